@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+
+use log::debug;
+use reqwest::{
+    blocking::RequestBuilder,
+    header::{ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Error,
+    file::{get_cache_dir, read_file, save_to_file},
+};
+
+const PAGES_DIR_NAME: &str = "pages";
+
+/// How pages are taken from the cache.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CacheMode {
+    /// Ask the site whether the cached page changed, and fall back to it when
+    /// the site cannot be reached.
+    #[default]
+    Revalidate,
+    /// Only the cached pages, without going online.
+    Offline,
+    /// Fetch every page again, ignoring the cached ones.
+    Refresh,
+}
+
+/// Pages fetched before, saved in the user cache directory with the
+/// validators the site sent for them.
+pub struct PageCache {
+    dir: PathBuf,
+    mode: CacheMode,
+}
+
+/// What is saved next to the body of a page.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+    fetched_at: String,
+}
+
+pub struct CachedPage {
+    entry: Entry,
+    pub body: String,
+}
+
+impl CachedPage {
+    pub fn fetched_at(&self) -> &str {
+        &self.entry.fetched_at
+    }
+
+    /// Makes the request return 304 Not Modified when the page is unchanged.
+    pub fn conditional(&self, builder: RequestBuilder) -> RequestBuilder {
+        let builder = match &self.entry.etag {
+            Some(etag) => builder.header(IF_NONE_MATCH, etag),
+            None => builder,
+        };
+        match &self.entry.last_modified {
+            Some(last_modified) => builder.header(IF_MODIFIED_SINCE, last_modified),
+            None => builder,
+        }
+    }
+}
+
+impl PageCache {
+    pub fn open(mode: CacheMode) -> Result<Self, Error> {
+        Ok(Self::in_dir(get_cache_dir()?.join(PAGES_DIR_NAME), mode))
+    }
+
+    pub fn in_dir(dir: PathBuf, mode: CacheMode) -> Self {
+        Self { dir, mode }
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    /// The cached page of `url`, none when refreshing.
+    pub fn lookup(&self, url: &str) -> Option<CachedPage> {
+        if self.mode == CacheMode::Refresh {
+            return None;
+        }
+
+        let (entry_path, body_path) = self.paths(url);
+        let entry = read_file(&entry_path.to_string_lossy())
+            .ok()
+            .and_then(|contents| toml::from_str::<Entry>(&contents).ok())
+            .filter(|entry| entry.url == url)?;
+        let body = read_file(&body_path.to_string_lossy()).ok()?;
+        debug!("{} is cached at {}", url, entry.fetched_at);
+
+        Some(CachedPage { entry, body })
+    }
+
+    pub fn store(&self, url: &str, headers: &HeaderMap, body: &str) -> Result<(), Error> {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let entry = Entry {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            fetched_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        };
+
+        let (entry_path, body_path) = self.paths(url);
+        save_to_file(&body_path, body)?;
+        let contents = toml::to_string(&entry).map_err(|e| Error::Internal(e.to_string()))?;
+        save_to_file(&entry_path, &contents)?;
+        debug!("cache {} at {:?}", url, body_path);
+
+        Ok(())
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = file_key(url);
+        (
+            self.dir.join(format!("{}.toml", key)),
+            self.dir.join(format!("{}.html", key)),
+        )
+    }
+}
+
+/// A file name for `url`. Different URLs may share one, which is why the URL
+/// is saved in the entry too.
+fn file_key(url: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    url.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use std::{env, fs};
+
+    #[test]
+    fn test_store_and_lookup() {
+        let dir = env::temp_dir().join(format!("rlr-cache-{}", std::process::id()));
+        let url = "https://recursionist.io/dashboard/problems/1";
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+
+        let cache = PageCache::in_dir(dir.clone(), CacheMode::Revalidate);
+        assert!(cache.lookup(url).is_none());
+        cache.store(url, &headers, "<html></html>").unwrap();
+
+        let page = cache.lookup(url).unwrap();
+        assert_eq!(page.body, "<html></html>");
+        assert_eq!(page.entry.etag.as_deref(), Some("\"abc\""));
+        assert!(page.entry.last_modified.is_none());
+        assert!(
+            cache
+                .lookup("https://recursionist.io/dashboard/problems_1")
+                .is_none()
+        );
+
+        let refresh = PageCache::in_dir(dir.clone(), CacheMode::Refresh);
+        assert!(refresh.lookup(url).is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_key() {
+        assert_eq!(
+            file_key("https://recursionist.io/dashboard/problems/1?tab=2"),
+            "recursionist.io_dashboard_problems_1_tab_2"
+        );
+    }
+}
@@ -0,0 +1,523 @@
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use colored::Colorize;
+use log::{debug, warn};
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Config,
+    error::Error,
+    file::{get_cache_dir, read_file, save_to_file},
+    i18n::{Msg, tr},
+    messages::{INFO_LABEL, print_table},
+    parser::{CATEGORY_LABELS, DIFFICULTY_LABELS, collapse_text, pickup_labeled},
+    request::fetch_problem_index,
+    site::Site,
+};
+
+const CATALOG_FILE_NAME: &str = "problems.toml";
+const STATUS_LABELS: &[&str] = &["状態", "ステータス", "解答状況", "Status"];
+const TITLE_LABELS: &[&str] = &["タイトル", "問題名", "Title"];
+
+/// Difficulty names in ascending order, for `--sort difficulty`. Names not
+/// listed here come after them, in text order.
+const DIFFICULTY_ORDER: &[&[&str]] = &[
+    &["入門", "beginner"],
+    &["初級", "easy"],
+    &["中級", "medium", "normal"],
+    &["上級", "hard"],
+];
+
+/// One problem of the dashboard index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemSummary {
+    pub id: u32,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<String>,
+    /// Whether the logged in user has solved it.
+    pub solved: bool,
+}
+
+/// The index as cached in the user cache directory, for `list --offline`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Catalog {
+    fetched_at: String,
+    problems: Vec<ProblemSummary>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SortKey {
+    #[default]
+    Id,
+    Title,
+    Category,
+    Difficulty,
+}
+
+pub struct ListOptions {
+    /// Show the cached index without going online.
+    pub offline: bool,
+    pub category: Option<String>,
+    pub difficulty: Option<String>,
+    /// Only the solved (`true`) or unsolved (`false`) problems.
+    pub solved: Option<bool>,
+    /// Text searched for in the titles.
+    pub search: Option<String>,
+    pub sort: SortKey,
+    pub reverse: bool,
+}
+
+/// Shows the problem index of the dashboard, fetched with the saved login, or
+/// the cached one when offline.
+pub fn list(options: &ListOptions, config: &Config) -> Result<(), Error> {
+    let catalog = if options.offline {
+        load_cached()?
+    } else {
+        match fetch_catalog(config) {
+            Ok(catalog) => catalog,
+            Err(e) if e.is_unreachable() => {
+                warn!(
+                    "failed to fetch the problem index, the cache is used: {}",
+                    e
+                );
+                load_cached()?
+            }
+            Err(e @ Error::EmptyProblemIndex(_)) if catalog_path()?.exists() => {
+                warn!("{}, the cache is used", e);
+                load_cached()?
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    let mut problems = catalog
+        .problems
+        .into_iter()
+        .filter(|problem| options.matches(problem))
+        .collect::<Vec<_>>();
+    sort_problems(&mut problems, options.sort);
+    if options.reverse {
+        problems.reverse();
+    }
+
+    println!(
+        "[{}] {}",
+        *INFO_LABEL,
+        tr!(Msg::ProblemsListed, problems.len(), catalog.fetched_at)
+    );
+    println!();
+
+    let rows = problems
+        .iter()
+        .map(|problem| {
+            vec![
+                problem.id.to_string(),
+                problem.title.clone(),
+                problem.category.clone().unwrap_or_else(|| "-".to_string()),
+                problem
+                    .difficulty
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+                if problem.solved { "yes" } else { "-" }.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_table(
+        &["id", "title", "category", "difficulty", "solved"],
+        &rows,
+        |row, column, cell| {
+            if column == 4 && problems[row].solved {
+                cell.green().to_string()
+            } else {
+                cell.to_string()
+            }
+        },
+    );
+
+    Ok(())
+}
+
+impl ListOptions {
+    fn matches(&self, problem: &ProblemSummary) -> bool {
+        let contains = |value: &Option<String>, filter: &Option<String>| match filter {
+            Some(filter) => value
+                .as_deref()
+                .is_some_and(|value| value.to_lowercase().contains(&filter.to_lowercase())),
+            None => true,
+        };
+
+        contains(&problem.category, &self.category)
+            && contains(&problem.difficulty, &self.difficulty)
+            && contains(&Some(problem.title.clone()), &self.search)
+            && self.solved.is_none_or(|solved| problem.solved == solved)
+    }
+}
+
+fn sort_problems(problems: &mut [ProblemSummary], key: SortKey) {
+    problems.sort_by_key(|problem| problem.id);
+    match key {
+        SortKey::Id => {}
+        SortKey::Title => problems.sort_by(|a, b| a.title.cmp(&b.title)),
+        // problems without the value go last
+        SortKey::Category => {
+            problems.sort_by_key(|problem| (problem.category.is_none(), problem.category.clone()))
+        }
+        SortKey::Difficulty => problems.sort_by_key(|problem| {
+            let rank = problem.difficulty.as_deref().map(difficulty_rank);
+            (rank.is_none(), rank, problem.difficulty.clone())
+        }),
+    }
+}
+
+fn difficulty_rank(difficulty: &str) -> usize {
+    let difficulty = difficulty.to_lowercase();
+    DIFFICULTY_ORDER
+        .iter()
+        .position(|names| names.iter().any(|name| difficulty.contains(name)))
+        .unwrap_or(DIFFICULTY_ORDER.len())
+}
+
+/// Fetches the index and saves it as the cache. A cache fetched before is
+/// kept when no problem is found, as the layout of the site may have changed.
+fn fetch_catalog(config: &Config) -> Result<Catalog, Error> {
+    let site = config.site()?;
+    let problems = collect_problems(&fetch_problem_index(config)?, &site)?;
+
+    let catalog = Catalog {
+        fetched_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        problems,
+    };
+    let contents = toml::to_string(&catalog).map_err(|e| Error::Internal(e.to_string()))?;
+    let path = catalog_path()?;
+    save_to_file(&path, &contents)?;
+    debug!("save the problem index to {:?}", path);
+
+    Ok(catalog)
+}
+
+/// The problems of every page of the index, each only once.
+fn collect_problems(pages: &[String], site: &Site) -> Result<Vec<ProblemSummary>, Error> {
+    let mut problems = Vec::new();
+    for page in pages {
+        for problem in parse_problem_index(page, site)? {
+            if !problems.iter().any(|p: &ProblemSummary| p.id == problem.id) {
+                problems.push(problem);
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        return Err(Error::EmptyProblemIndex(site.problem_index_url()));
+    }
+    Ok(problems)
+}
+
+fn load_cached() -> Result<Catalog, Error> {
+    let path = catalog_path()?;
+    if !path.exists() {
+        return Err(Error::NoCatalogCache);
+    }
+
+    let contents = read_file(&path.to_string_lossy())?;
+    let catalog =
+        toml::from_str(&contents).map_err(|e| Error::Config(format!("{:?}: {}", path, e)))?;
+    println!("[{}] {}", *INFO_LABEL, Msg::CatalogFromCache);
+    Ok(catalog)
+}
+
+fn catalog_path() -> Result<PathBuf, Error> {
+    Ok(get_cache_dir()?.join(CATALOG_FILE_NAME))
+}
+
+/// The URL of the next page of the index, from a `rel="next"` link.
+pub fn next_page_url(html: &str, current: &str) -> Result<Option<String>, Error> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[rel~=next], link[rel~=next]")
+        .map_err(|e| Error::Selector(e.to_string().into()))?;
+
+    let Some(href) = document
+        .select(&selector)
+        .find_map(|elem| elem.value().attr("href"))
+    else {
+        return Ok(None);
+    };
+
+    let base = Url::parse(current).map_err(|_| Error::UrlIncorrectFormat)?;
+    Ok(base.join(href).ok().map(|url| url.to_string()))
+}
+
+/// Reads the problems of one index page. Every link to a problem is a
+/// problem; its row is the largest element around the link that holds no
+/// other problem, and the category, difficulty and status are read from the
+/// columns of a table, labels in the row or `data-*` attributes.
+pub fn parse_problem_index(html: &str, site: &Site) -> Result<Vec<ProblemSummary>, Error> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[href]").map_err(|e| Error::Selector(e.to_string().into()))?;
+    let problem_link = site.problem_link()?;
+
+    let link_id = |element: ElementRef| -> Option<u32> {
+        let href = element.value().attr("href")?;
+        problem_link.captures(href)?[1].parse().ok()
+    };
+
+    let mut problems = Vec::<ProblemSummary>::new();
+    for link in document.select(&selector) {
+        let Some(id) = link_id(link) else {
+            continue;
+        };
+        if problems.iter().any(|p| p.id == id) {
+            continue;
+        }
+
+        let mut row = link;
+        for ancestor in link.ancestors().filter_map(ElementRef::wrap) {
+            let other_problem = ancestor
+                .select(&selector)
+                .any(|other| link_id(other).is_some_and(|other| other != id));
+            if other_problem || ancestor.value().name() == "body" {
+                break;
+            }
+            row = ancestor;
+        }
+
+        let columns = table_columns(row);
+        let column = |labels: &[&str]| -> Option<String> {
+            columns
+                .iter()
+                .find(|(header, _)| labels.iter().any(|label| header.starts_with(label)))
+                .map(|(_, value)| value.clone())
+                .filter(|value| !value.is_empty())
+        };
+        let labeled = |labels: &[&str], attr: &str| {
+            column(labels)
+                .or_else(|| pickup_labeled(row, labels))
+                .or_else(|| data_attr(row, attr))
+        };
+
+        let title = Some(collapse_text(link))
+            .filter(|title| !title.is_empty())
+            .or_else(|| column(TITLE_LABELS))
+            .or_else(|| link.value().attr("title").map(|title| title.to_string()))
+            .unwrap_or_default();
+
+        problems.push(ProblemSummary {
+            id,
+            title,
+            category: labeled(CATEGORY_LABELS, "data-category"),
+            difficulty: labeled(DIFFICULTY_LABELS, "data-difficulty"),
+            solved: is_solved(row, labeled(STATUS_LABELS, "data-status").as_deref()),
+        });
+    }
+
+    Ok(problems)
+}
+
+/// The cells of a table row paired with the headers of their columns, or
+/// nothing when the row is not in a table.
+fn table_columns(row: ElementRef) -> Vec<(String, String)> {
+    if row.value().name() != "tr" {
+        return Vec::new();
+    }
+    let Some(table) = row
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().name() == "table")
+    else {
+        return Vec::new();
+    };
+
+    let cells = |element: ElementRef| {
+        element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|cell| matches!(cell.value().name(), "th" | "td"))
+            .map(collapse_text)
+            .collect::<Vec<_>>()
+    };
+    let Some(headers) = table
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "tr")
+        .find(|tr| {
+            tr.children()
+                .filter_map(ElementRef::wrap)
+                .any(|c| c.value().name() == "th")
+        })
+        .map(cells)
+    else {
+        return Vec::new();
+    };
+
+    headers.into_iter().zip(cells(row)).collect()
+}
+
+fn data_attr(row: ElementRef, attr: &str) -> Option<String> {
+    row.descendants()
+        .filter_map(ElementRef::wrap)
+        .find_map(|e| e.value().attr(attr))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Decides from the status text, or else from a `data-solved` attribute or
+/// a class such as `solved` or `is-completed` in the row.
+fn is_solved(row: ElementRef, status: Option<&str>) -> bool {
+    if let Some(solved) = status.and_then(solved_from_text) {
+        return solved;
+    }
+    if let Some(value) = data_attr(row, "data-solved") {
+        return matches!(value.as_str(), "true" | "1" | "yes");
+    }
+
+    row.descendants()
+        .filter_map(ElementRef::wrap)
+        .flat_map(|e| e.value().classes())
+        .any(|class| {
+            let parts = class
+                .split(['-', '_'])
+                .map(|part| part.to_lowercase())
+                .collect::<Vec<_>>();
+            parts
+                .iter()
+                .any(|part| matches!(part.as_str(), "solved" | "completed" | "cleared"))
+                && !parts
+                    .iter()
+                    .any(|part| matches!(part.as_str(), "not" | "un" | "unsolved"))
+        })
+}
+
+fn solved_from_text(text: &str) -> Option<bool> {
+    let text = text.to_lowercase();
+    if ["未", "unsolved", "not"]
+        .iter()
+        .any(|word| text.contains(word))
+    {
+        Some(false)
+    } else if [
+        "正解",
+        "解答済",
+        "クリア",
+        "完了",
+        "済",
+        "solved",
+        "completed",
+        "accepted",
+    ]
+    .iter()
+    .any(|word| text.contains(word))
+    {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX_PAGE: &str = include_str!("../tests/fixtures/problem-index.html");
+
+    fn summary(
+        id: u32,
+        title: &str,
+        category: &str,
+        difficulty: &str,
+        solved: bool,
+    ) -> ProblemSummary {
+        ProblemSummary {
+            id,
+            title: title.to_string(),
+            category: Some(category.to_string()),
+            difficulty: Some(difficulty.to_string()),
+            solved,
+        }
+    }
+
+    #[test]
+    fn test_parse_problem_index_table() {
+        assert_eq!(
+            parse_problem_index(INDEX_PAGE, &Site::default()).unwrap(),
+            [
+                summary(1, "最低気温", "数学", "初級", true),
+                summary(2, "文字列の反転", "文字列", "中級", false),
+                summary(3, "二分探索", "探索", "上級", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_problem_index_cards() {
+        let html = r#"<ul>
+            <li class="card is-solved"><a href="/dashboard/problems/7">Fizz Buzz</a>
+                <span>カテゴリー: 制御フロー</span><span>難易度: 入門</span></li>
+            <li class="card"><a href="/dashboard/problems/8">
+                <h3>Sum</h3></a><p data-difficulty="初級" data-category="配列"></p></li>
+        </ul>"#;
+
+        assert_eq!(
+            parse_problem_index(html, &Site::default()).unwrap(),
+            [
+                summary(7, "Fizz Buzz", "制御フロー", "入門", true),
+                summary(8, "Sum", "配列", "初級", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_problems() {
+        let site = Site::default();
+        let pages = [INDEX_PAGE.to_string(), INDEX_PAGE.to_string()];
+        assert_eq!(collect_problems(&pages, &site).unwrap().len(), 3);
+
+        assert!(matches!(
+            collect_problems(&["<p>maintenance</p>".to_string()], &site),
+            Err(Error::EmptyProblemIndex(url)) if url == site.problem_index_url()
+        ));
+        assert!(matches!(
+            collect_problems(&[], &site),
+            Err(Error::EmptyProblemIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_next_page_url() {
+        assert_eq!(
+            next_page_url(INDEX_PAGE, "https://recursionist.io/dashboard/problems").unwrap(),
+            Some("https://recursionist.io/dashboard/problems?page=2".to_string())
+        );
+        assert_eq!(
+            next_page_url("<p></p>", "https://recursionist.io/dashboard/problems").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let mut problems = vec![
+            summary(3, "c", "探索", "上級", false),
+            summary(1, "a", "数学", "初級", true),
+            summary(2, "b", "文字列", "中級", false),
+        ];
+        let options = ListOptions {
+            offline: true,
+            category: None,
+            difficulty: None,
+            solved: Some(false),
+            search: None,
+            sort: SortKey::Difficulty,
+            reverse: false,
+        };
+
+        problems.retain(|problem| options.matches(problem));
+        sort_problems(&mut problems, options.sort);
+
+        assert_eq!(problems.iter().map(|p| p.id).collect::<Vec<_>>(), [2, 3]);
+    }
+}
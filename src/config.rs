@@ -0,0 +1,681 @@
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use toml::{Table, Value};
+
+use crate::{
+    error::Error,
+    file::{get_config_dir, save_to_file},
+    judge::Comparator,
+    language::Profile,
+    layout::InputLayout,
+    literal::Preset,
+    network::{
+        DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_READ_TIMEOUT_SECS, DEFAULT_RETRIES, NetworkSettings,
+        Proxies,
+    },
+    site::{BASE_URL_ENV, Site},
+};
+
+pub const CONFIG_FILE_NAME: &str = "rlr.toml";
+
+const DEFAULT_TIMEOUT_SECS: f64 = 3.0;
+const DEFAULT_TEMPLATE_DIR: &str = "template";
+const DEFAULT_PROBLEM_DIR_PREFIX: &str = "p-";
+const DEFAULT_TESTCASE_DIR: &str = "testcase";
+const DEFAULT_DOWNLOAD_JOBS: usize = 2;
+const DEFAULT_DOWNLOAD_INTERVAL_SECS: f64 = 1.0;
+
+/// Settings read from `rlr.toml`. Every field is optional so that the layers
+/// (user config dir, workspace, problem directory) can be merged field by field.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub judge: JudgeConfig,
+    pub download: DownloadConfig,
+    pub naming: NamingConfig,
+    pub site: SiteConfig,
+    pub network: NetworkConfig,
+    /// User-defined language profiles, keyed by profile name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JudgeConfig {
+    /// Command used when `rlr judge` is run without one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Command run once before judging, e.g. a compiler.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
+    /// Time limit per case in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparator: Option<Comparator>,
+    /// How the language prints values, for `comparator = "value"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<Preset>,
+    /// Language profile used when no command is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DownloadConfig {
+    /// Directory copied into each downloaded problem directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// How the arguments are written to the `.in` files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<InputLayout>,
+    /// Pages fetched at the same time when downloading several problems.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+    /// Seconds between two requests when downloading several problems.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<f64>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamingConfig {
+    /// Prefix of the directory created for each problem (`p-` gives `p-1`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem_dir_prefix: Option<String>,
+    /// Directory holding the test case files inside a problem directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub testcase_dir: Option<String>,
+}
+
+/// Where the pages of the site are, for a mirror or a local stand-in server.
+/// Paths with `{id}` are patterns for a problem or submission number.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SiteConfig {
+    /// `https://recursionist.io` unless set here or in `RLR_BASE_URL`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub login_path: Option<String>,
+    /// Where a successful login redirects to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dashboard_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem_index_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submission_path: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Seconds to wait for a connection to the site.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<f64>,
+    /// Seconds to wait for a response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<f64>,
+    /// Times a page is fetched again after a transient failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Proxy for every request, over the proxy environment variables. An
+    /// empty one connects directly.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Hosts reached without the proxy, as in `NO_PROXY`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// PEM files of root certificates trusted besides the built-in ones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_certs: Option<Vec<String>>,
+    /// PEM file of the only certificate trusted, for a local test server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_cert: Option<String>,
+    /// Skips verifying certificates, for a local test server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insecure: Option<bool>,
+}
+
+impl Config {
+    /// Loads and merges every config layer, later layers taking precedence.
+    pub fn load() -> Result<Self, Error> {
+        let mut config = Config::default();
+
+        for path in config_paths()? {
+            debug!("load config: {:?}", path);
+            config.merge(read_config(&path)?);
+        }
+
+        Ok(config)
+    }
+
+    /// Rejects the values that cannot be used, such as a negative time limit.
+    fn validate(&self) -> Result<(), String> {
+        let seconds = [
+            ("judge.timeout", self.judge.timeout, false),
+            ("download.interval", self.download.interval, true),
+            (
+                "network.connect_timeout",
+                self.network.connect_timeout,
+                false,
+            ),
+            ("network.timeout", self.network.timeout, false),
+        ];
+        for (key, value, zero_allowed) in seconds {
+            if let Some(value) = value {
+                let valid = value.is_finite() && (value > 0.0 || zero_allowed && value == 0.0);
+                if !valid {
+                    return Err(format!(
+                        "{} must be a positive number of seconds: {}",
+                        key, value
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// This config with the defaults filled in, i.e. the settings in effect.
+    /// The fields without a default, such as `judge.command`, stay unset.
+    fn resolved(&self) -> Result<Config, Error> {
+        let network = self.network();
+        let resolved = Config {
+            judge: JudgeConfig {
+                timeout: Some(self.timeout().as_secs_f64()),
+                comparator: Some(self.comparator()),
+                preset: Some(self.preset()),
+                ..self.judge.clone()
+            },
+            download: DownloadConfig {
+                template: Some(self.template_dir().to_string()),
+                layout: Some(self.download.layout.unwrap_or_default()),
+                jobs: Some(self.download_jobs()),
+                interval: Some(self.download_interval().as_secs_f64()),
+            },
+            naming: NamingConfig {
+                problem_dir_prefix: Some(
+                    self.naming
+                        .problem_dir_prefix
+                        .clone()
+                        .unwrap_or(DEFAULT_PROBLEM_DIR_PREFIX.to_string()),
+                ),
+                testcase_dir: Some(self.testcase_dir().to_string()),
+            },
+            site: self.site()?.to_config(),
+            network: NetworkConfig {
+                connect_timeout: Some(network.connect_timeout.as_secs_f64()),
+                timeout: Some(network.read_timeout.as_secs_f64()),
+                retries: Some(network.retries),
+                ca_certs: Some(self.network.ca_certs.clone().unwrap_or_default()),
+                insecure: Some(network.insecure),
+                ..self.network.clone()
+            },
+            profiles: self.profiles.clone(),
+        };
+        Ok(resolved)
+    }
+
+    fn merge(&mut self, other: Config) {
+        merge_option(&mut self.judge.command, other.judge.command);
+        merge_option(&mut self.judge.build, other.judge.build);
+        merge_option(&mut self.judge.timeout, other.judge.timeout);
+        merge_option(&mut self.judge.comparator, other.judge.comparator);
+        merge_option(&mut self.judge.preset, other.judge.preset);
+        merge_option(&mut self.judge.profile, other.judge.profile);
+        merge_option(&mut self.download.template, other.download.template);
+        merge_option(&mut self.download.layout, other.download.layout);
+        merge_option(&mut self.download.jobs, other.download.jobs);
+        merge_option(&mut self.download.interval, other.download.interval);
+        merge_option(
+            &mut self.naming.problem_dir_prefix,
+            other.naming.problem_dir_prefix,
+        );
+        merge_option(&mut self.naming.testcase_dir, other.naming.testcase_dir);
+        merge_option(&mut self.site.base_url, other.site.base_url);
+        merge_option(&mut self.site.login_path, other.site.login_path);
+        merge_option(&mut self.site.dashboard_path, other.site.dashboard_path);
+        merge_option(&mut self.site.problem_path, other.site.problem_path);
+        merge_option(
+            &mut self.site.problem_index_path,
+            other.site.problem_index_path,
+        );
+        merge_option(&mut self.site.submit_path, other.site.submit_path);
+        merge_option(&mut self.site.submission_path, other.site.submission_path);
+        merge_option(
+            &mut self.network.connect_timeout,
+            other.network.connect_timeout,
+        );
+        merge_option(&mut self.network.timeout, other.network.timeout);
+        merge_option(&mut self.network.retries, other.network.retries);
+        merge_option(&mut self.network.proxy, other.network.proxy);
+        merge_option(&mut self.network.no_proxy, other.network.no_proxy);
+        merge_option(&mut self.network.ca_certs, other.network.ca_certs);
+        merge_option(&mut self.network.pinned_cert, other.network.pinned_cert);
+        merge_option(&mut self.network.insecure, other.network.insecure);
+        self.profiles.extend(other.profiles);
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs_f64(self.judge.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+
+    pub fn comparator(&self) -> Comparator {
+        self.judge.comparator.unwrap_or_default()
+    }
+
+    pub fn preset(&self) -> Preset {
+        self.judge.preset.unwrap_or_default()
+    }
+
+    /// The site from `[site]`, with the base URL overridden by `RLR_BASE_URL`.
+    pub fn site(&self) -> Result<Site, Error> {
+        Site::from_config(&self.site, env::var(BASE_URL_ENV).ok())
+    }
+
+    pub fn network(&self) -> NetworkSettings {
+        let secs = |value: Option<f64>, default| Duration::from_secs_f64(value.unwrap_or(default));
+        NetworkSettings {
+            connect_timeout: secs(self.network.connect_timeout, DEFAULT_CONNECT_TIMEOUT_SECS),
+            read_timeout: secs(self.network.timeout, DEFAULT_READ_TIMEOUT_SECS),
+            retries: self.network.retries.unwrap_or(DEFAULT_RETRIES),
+            proxies: self.proxies(),
+            ca_certs: self
+                .network
+                .ca_certs
+                .iter()
+                .flatten()
+                .map(PathBuf::from)
+                .collect(),
+            pinned_cert: self.network.pinned_cert.as_ref().map(PathBuf::from),
+            insecure: self.network.insecure.unwrap_or(false),
+        }
+    }
+
+    /// `network.proxy`, or the proxies of the environment variables.
+    fn proxies(&self) -> Proxies {
+        match self.network.proxy.as_deref().map(str::trim) {
+            Some("") => Proxies::default(),
+            Some(url) => Proxies::all(url, self.network.no_proxy.clone()),
+            None => {
+                let mut proxies = Proxies::from_env(|name| env::var(name).ok());
+                if self.network.no_proxy.is_some() {
+                    proxies.no_proxy = self.network.no_proxy.clone();
+                }
+                proxies
+            }
+        }
+    }
+
+    pub fn download_jobs(&self) -> usize {
+        self.download.jobs.unwrap_or(DEFAULT_DOWNLOAD_JOBS)
+    }
+
+    pub fn download_interval(&self) -> Duration {
+        Duration::from_secs_f64(
+            self.download
+                .interval
+                .unwrap_or(DEFAULT_DOWNLOAD_INTERVAL_SECS),
+        )
+    }
+
+    pub fn template_dir(&self) -> &str {
+        self.download
+            .template
+            .as_deref()
+            .unwrap_or(DEFAULT_TEMPLATE_DIR)
+    }
+
+    pub fn problem_dir(&self, problem_id: &str) -> String {
+        let prefix = self
+            .naming
+            .problem_dir_prefix
+            .as_deref()
+            .unwrap_or(DEFAULT_PROBLEM_DIR_PREFIX);
+        format!("./{}{}", prefix, problem_id)
+    }
+
+    pub fn testcase_dir(&self) -> &str {
+        self.naming
+            .testcase_dir
+            .as_deref()
+            .unwrap_or(DEFAULT_TESTCASE_DIR)
+    }
+}
+
+fn merge_option<T>(base: &mut Option<T>, other: Option<T>) {
+    if other.is_some() {
+        *base = other;
+    }
+}
+
+fn read_config(path: &Path) -> Result<Config, Error> {
+    let contents = fs::read_to_string(path)?;
+    parse_config(&contents).map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))
+}
+
+fn parse_config(contents: &str) -> Result<Config, String> {
+    let config = toml::from_str::<Config>(contents).map_err(|e| e.to_string())?;
+    config.validate()?;
+    Ok(config)
+}
+
+pub fn user_config_path() -> Result<PathBuf, Error> {
+    Ok(get_config_dir()?.join(CONFIG_FILE_NAME))
+}
+
+/// The user config followed by every `rlr.toml` from the outermost ancestor
+/// of the current directory (the workspace) down to the current directory
+/// (usually a problem directory).
+pub fn config_paths() -> Result<Vec<PathBuf>, Error> {
+    let user_path = user_config_path()?;
+    let cwd = env::current_dir()?;
+
+    let mut local = cwd
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .filter(|path| path.is_file() && *path != user_path)
+        .collect::<Vec<_>>();
+    local.reverse();
+
+    let mut paths = Vec::new();
+    if user_path.is_file() {
+        paths.push(user_path);
+    }
+    paths.extend(local);
+    Ok(paths)
+}
+
+pub fn config_get(key: &str) -> Result<(), Error> {
+    let table = resolved_table(&Config::load()?)?;
+
+    let value = lookup(&table, key).ok_or(Error::ConfigKeyNotFound(key.to_string()))?;
+    match value {
+        Value::String(s) => println!("{}", s),
+        other => println!("{}", other),
+    }
+    Ok(())
+}
+
+pub fn config_set(key: &str, value: &str, global: bool) -> Result<(), Error> {
+    let path = if global {
+        user_config_path()?
+    } else {
+        env::current_dir()?.join(CONFIG_FILE_NAME)
+    };
+
+    let mut table = if path.is_file() {
+        fs::read_to_string(&path)?
+            .parse::<Table>()
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?
+    } else {
+        Table::new()
+    };
+
+    let contents = set_value(&mut table, key, value)?;
+    save_to_file(&path, &contents)?;
+    println!("{} = {} ({})", key, value, path.display());
+    Ok(())
+}
+
+pub fn config_list() -> Result<(), Error> {
+    for path in config_paths()? {
+        println!("# {}", path.display());
+    }
+
+    let table = resolved_table(&Config::load()?)?;
+    for (key, value) in flatten(&table, "") {
+        println!("{} = {}", key, value);
+    }
+    Ok(())
+}
+
+fn resolved_table(config: &Config) -> Result<Table, Error> {
+    Table::try_from(config.resolved()?).map_err(|e| Error::Config(e.to_string()))
+}
+
+/// Sets `key` to `value` in `table`, typed as the setting expects, and returns
+/// the new contents of the file. A value like `123` becomes an integer for a
+/// number setting and stays a string for a command.
+fn set_value(table: &mut Table, key: &str, value: &str) -> Result<String, Error> {
+    let mut first_error = None;
+    for candidate in value_candidates(value) {
+        let mut updated = table.clone();
+        insert(&mut updated, key, candidate)?;
+        let contents = toml::to_string(&updated).map_err(|e| Error::Config(e.to_string()))?;
+
+        // reject keys and values that the config does not understand
+        match parse_config(&contents) {
+            Ok(_) => {
+                *table = updated;
+                return Ok(contents);
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(Error::Config(format!(
+        "{}: {}",
+        key,
+        first_error.unwrap_or_default()
+    )))
+}
+
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+    Some(value)
+}
+
+fn insert(table: &mut Table, key: &str, value: Value) -> Result<(), Error> {
+    let parts = key.split('.').collect::<Vec<_>>();
+    let (last, sections) = parts
+        .split_last()
+        .ok_or(Error::ConfigKeyNotFound(key.to_string()))?;
+
+    let mut current = table;
+    for section in sections {
+        current = current
+            .entry(section.to_string())
+            .or_insert_with(|| Value::Table(Table::new()))
+            .as_table_mut()
+            .ok_or(Error::ConfigKeyNotFound(key.to_string()))?;
+    }
+    current.insert(last.to_string(), value);
+    Ok(())
+}
+
+fn flatten(table: &Table, prefix: &str) -> Vec<(String, Value)> {
+    let mut entries = Vec::new();
+    for (key, value) in table {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Table(inner) => entries.extend(flatten(inner, &full_key)),
+            other => entries.push((full_key, other.clone())),
+        }
+    }
+    entries
+}
+
+/// The TOML values a command line value may stand for, the type it looks
+/// like first. The setting decides which one is taken.
+fn value_candidates(value: &str) -> Vec<Value> {
+    let mut candidates = Vec::new();
+    if let Ok(i) = value.parse::<i64>() {
+        candidates.push(Value::Integer(i));
+    }
+    if let Ok(f) = value.parse::<f64>() {
+        candidates.push(Value::Float(f));
+    }
+    if let Ok(b) = value.parse::<bool>() {
+        candidates.push(Value::Boolean(b));
+    }
+    candidates.push(Value::String(value.to_string()));
+    candidates.push(Value::Array(vec![Value::String(value.to_string())]));
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let contents = r#"
+            [judge]
+            command = "python3 main.py"
+            timeout = 5
+            comparator = "tokens"
+
+            [naming]
+            problem_dir_prefix = "problem-"
+        "#;
+
+        let config: Config = toml::from_str(contents).unwrap();
+
+        assert_eq!(config.judge.command.as_deref(), Some("python3 main.py"));
+        assert_eq!(config.timeout(), Duration::from_secs(5));
+        assert_eq!(config.comparator(), Comparator::Tokens);
+        assert_eq!(config.problem_dir("1"), "./problem-1");
+        assert_eq!(config.testcase_dir(), "testcase");
+        assert_eq!(config.template_dir(), "template");
+    }
+
+    #[test]
+    fn test_unknown_key_is_rejected() {
+        let contents = r#"
+            [judge]
+            comand = "python3 main.py"
+        "#;
+
+        assert!(toml::from_str::<Config>(contents).is_err());
+    }
+
+    #[test]
+    fn test_invalid_seconds_are_rejected() {
+        for timeout in ["-1", "0", "nan", "inf"] {
+            let contents = format!("[judge]\ntimeout = {}", timeout);
+            assert!(parse_config(&contents).is_err(), "timeout = {}", timeout);
+        }
+        assert!(parse_config("[network]\nconnect_timeout = -5").is_err());
+        assert!(parse_config("[download]\ninterval = 0").is_ok());
+        assert!(parse_config("[judge]\ntimeout = 0.5").is_ok());
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut base: Config = toml::from_str(
+            r#"
+            [judge]
+            command = "python3 main.py"
+            timeout = 2
+            "#,
+        )
+        .unwrap();
+        let problem: Config = toml::from_str(
+            r#"
+            [judge]
+            command = "./main"
+            "#,
+        )
+        .unwrap();
+
+        base.merge(problem);
+
+        assert_eq!(base.judge.command.as_deref(), Some("./main"));
+        assert_eq!(base.timeout(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_resolved_has_the_defaults() {
+        let config: Config = toml::from_str("[judge]\ncommand = \"./main\"").unwrap();
+        let table = resolved_table(&config).unwrap();
+
+        assert_eq!(lookup(&table, "judge.timeout"), Some(&Value::Float(3.0)));
+        assert_eq!(
+            lookup(&table, "judge.command"),
+            Some(&Value::String("./main".to_string()))
+        );
+        assert_eq!(lookup(&table, "download.jobs"), Some(&Value::Integer(2)));
+        assert_eq!(
+            lookup(&table, "naming.problem_dir_prefix"),
+            Some(&Value::String("p-".to_string()))
+        );
+        assert_eq!(
+            lookup(&table, "site.problem_path"),
+            Some(&Value::String("/dashboard/problems/{id}".to_string()))
+        );
+        assert_eq!(lookup(&table, "network.retries"), Some(&Value::Integer(3)));
+        assert_eq!(lookup(&table, "judge.build"), None);
+    }
+
+    #[test]
+    fn test_set_value_follows_the_setting_type() {
+        let mut table = Table::new();
+        set_value(&mut table, "judge.command", "123").unwrap();
+        set_value(&mut table, "judge.timeout", "5").unwrap();
+        set_value(&mut table, "network.insecure", "true").unwrap();
+        set_value(&mut table, "network.ca_certs", "root.pem").unwrap();
+
+        assert_eq!(
+            lookup(&table, "judge.command"),
+            Some(&Value::String("123".to_string()))
+        );
+        assert_eq!(lookup(&table, "judge.timeout"), Some(&Value::Integer(5)));
+        assert_eq!(
+            lookup(&table, "network.insecure"),
+            Some(&Value::Boolean(true))
+        );
+        assert_eq!(
+            lookup(&table, "network.ca_certs"),
+            Some(&Value::Array(vec![Value::String("root.pem".to_string())]))
+        );
+
+        assert!(set_value(&mut table, "judge.timeout", "-1").is_err());
+        assert!(set_value(&mut table, "judge.timeout", "soon").is_err());
+        assert!(set_value(&mut table, "judge.comand", "./main").is_err());
+        assert_eq!(lookup(&table, "judge.timeout"), Some(&Value::Integer(5)));
+    }
+
+    #[test]
+    fn test_insert_and_lookup() {
+        let mut table = Table::new();
+        insert(&mut table, "judge.timeout", Value::Float(1.5)).unwrap();
+        insert(
+            &mut table,
+            "judge.command",
+            Value::String("./main".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(lookup(&table, "judge.timeout"), Some(&Value::Float(1.5)));
+        assert_eq!(
+            lookup(&table, "judge.command"),
+            Some(&Value::String("./main".to_string()))
+        );
+        assert_eq!(lookup(&table, "judge.build"), None);
+
+        let flattened = flatten(&table, "");
+        assert_eq!(flattened.len(), 2);
+    }
+}
@@ -1,6 +1,9 @@
+use std::io;
+
+use crate::i18n::{Msg, tr};
 use crate::messages::ERROR_LABEL;
 
-type SelectorParseError = Box<dyn std::error::Error>;
+type SelectorParseError = Box<dyn std::error::Error + Send + Sync>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -17,7 +20,25 @@ pub enum Error {
     CookiePathUnvaliable,
 
     #[error("Network error occurred: {0}")]
-    Network(#[from] reqwest::Error),
+    Network(reqwest::Error),
+
+    #[error("The site did not answer in time: {0}")]
+    Timeout(String),
+
+    #[error("Failed to resolve the host name: {0}")]
+    Dns(String),
+
+    #[error("TLS connection failed: {0} ({1})")]
+    Tls(String, String),
+
+    #[error("The site returned HTTP {0}: {1}")]
+    HttpStatus(u16, String),
+
+    #[error("Invalid proxy {0}: {1}")]
+    InvalidProxy(String, String),
+
+    #[error("Failed to read the certificate {0}: {1}")]
+    Certificate(String, String),
 
     #[error("Failed to retrieve cookies after login")]
     CookieMissing,
@@ -34,9 +55,6 @@ pub enum Error {
     #[error("Malformed cookie: {0}")]
     MalformedCookie(String),
 
-    #[error("No cookie file found")]
-    NoCookie,
-
     #[error("Cookie file is not valid UTF-8")]
     CookieNotUtf8,
 
@@ -44,10 +62,158 @@ pub enum Error {
     UrlIncorrectFormat,
 
     #[error("Authentication failed")]
-    AuthenticationError,
+    Unauthenticated,
 
     #[error("Cookie file not found")]
     CookieNotFound,
+
+    #[error("Invalid config: {0}")]
+    Config(String),
+
+    #[error("Config key not found: {0}")]
+    ConfigKeyNotFound(String),
+
+    #[error("No judge command given")]
+    NoJudgeCommand,
+
+    #[error("Build command failed: {0}")]
+    BuildFailed(String),
+
+    #[error("Unknown language profile: {0}")]
+    UnknownProfile(String),
+
+    #[error("Template not found: {0}")]
+    TemplateNotFound(String),
+
+    #[error("Template already exists: {0}")]
+    TemplateExists(String),
+
+    #[error("Invalid template name: {0}")]
+    InvalidTemplateName(String),
+
+    #[error("Invalid file name rendered from a template: {0}")]
+    InvalidRenderedName(String),
+
+    #[error("No stub for language: {0}")]
+    StubUnsupported(String),
+
+    #[error("Function mode is not supported for: {0}")]
+    FunctionUnsupported(String),
+
+    #[error("Problem number not found")]
+    NoProblemId,
+
+    #[error("Function name not found")]
+    NoFunctionName,
+
+    #[error("Function not found in the source: {0}")]
+    FunctionNotFound(String),
+
+    #[error("Invalid literal: {0}")]
+    Literal(String),
+
+    #[error("Invalid problem: {0}")]
+    InvalidTarget(String),
+
+    #[error("{0} downloads failed")]
+    DownloadsFailed(usize),
+
+    #[error("No cached problem index")]
+    NoCatalogCache,
+
+    #[error("No problem was found in the problem index: {0}")]
+    EmptyProblemIndex(String),
+
+    #[error("The page is not cached: {0}")]
+    NotCached(String),
+
+    #[error("Unexpected response from the site: {0}")]
+    UnexpectedResponse(String),
+
+    #[error("The verdict did not arrive in time")]
+    VerdictTimeout,
+
+    #[error("No source file to submit")]
+    NoSourceFile,
+
+    #[error("Unknown language of: {0}")]
+    NoLanguage(String),
+}
+
+/// Messages of a failed name lookup. hyper does not export the type of its
+/// DNS error, and the OS gives the lookup failure no `io::ErrorKind`, so this
+/// is a best-effort match on the text, tried after the typed checks.
+const DNS_MARKERS: &[&str] = &[
+    "dns error",
+    "failed to lookup address",
+    "name or service not known",
+];
+
+impl From<reqwest::Error> for Error {
+    /// Tells apart the failures the user can act on differently.
+    fn from(err: reqwest::Error) -> Self {
+        let url = err.url().map(|url| url.to_string()).unwrap_or_default();
+        if let Some(status) = err.status() {
+            return Error::HttpStatus(status.as_u16(), url);
+        }
+        if err.is_timeout() {
+            return Error::Timeout(url);
+        }
+        if err.is_connect()
+            && let Some(classified) = classify_connect_error(&err, &url)
+        {
+            return classified;
+        }
+        Error::Network(err)
+    }
+}
+
+/// Timeout, DNS or TLS for a failed connection, or `None` when it is some
+/// other network error. The typed sources are checked first.
+fn classify_connect_error(err: &reqwest::Error, url: &str) -> Option<Error> {
+    for cause in error_sources(err) {
+        if let Some(tls) = cause.downcast_ref::<native_tls::Error>() {
+            return Some(Error::Tls(url.to_string(), tls.to_string()));
+        }
+        if let Some(io) = cause.downcast_ref::<io::Error>() {
+            match io.kind() {
+                io::ErrorKind::TimedOut => return Some(Error::Timeout(url.to_string())),
+                io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::NetworkUnreachable
+                | io::ErrorKind::HostUnreachable => return None,
+                _ => {}
+            }
+        }
+    }
+
+    let is_dns = error_sources(err).any(|cause| {
+        let message = cause.to_string().to_lowercase();
+        DNS_MARKERS.iter().any(|marker| message.contains(marker))
+    });
+    is_dns.then(|| {
+        let host = err.url().and_then(|url| url.host_str()).unwrap_or_default();
+        Error::Dns(host.to_string())
+    })
+}
+
+/// The errors that caused `err`, innermost last.
+fn error_sources<'a>(
+    err: &'a (dyn std::error::Error + 'static),
+) -> impl Iterator<Item = &'a (dyn std::error::Error + 'static)> {
+    std::iter::successors(err.source(), |cause| cause.source())
+}
+
+impl Error {
+    /// Whether the site could not be reached, so that a cached copy may do.
+    pub fn is_unreachable(&self) -> bool {
+        match self {
+            Error::Network(_) | Error::Timeout(_) | Error::Dns(_) | Error::Tls(..) => true,
+            Error::HttpStatus(status, _) => *status >= 500,
+            _ => false,
+        }
+    }
 }
 
 macro_rules! errorln {
@@ -59,60 +225,144 @@ macro_rules! errorln {
 pub fn handle_error(e: Error) {
     match e {
         Error::Selector(err) => {
-            errorln!(
-                "テストケースの取得に失敗しました。ログインに失敗しているか、指定したURLが正しくない可能性があります: {}",
-                err,
-            );
+            errorln!("{}", tr!(Msg::ErrSelector, err));
         }
         Error::Io(err) => {
-            errorln!("I/O処理でエラーが発生しました: {}", err);
+            errorln!("{}", tr!(Msg::ErrIo, err));
         }
         Error::Internal(err) => {
-            errorln!("内部エラーが発生しました。");
+            errorln!("{}", Msg::ErrInternal);
             errorln!("{}", err);
         }
         Error::CookiePathUnvaliable => {
-            errorln!("Cookieファイルのパス取得に失敗しました。");
+            errorln!("{}", Msg::ErrCookiePath);
         }
         Error::Network(err) => {
-            errorln!("ネットワークエラーが発生しました。{}", err);
+            let detail = match error_sources(&err).last() {
+                Some(cause) => format!("{} ({})", err, cause),
+                None => err.to_string(),
+            };
+            errorln!("{}", tr!(Msg::ErrNetwork, detail));
+        }
+        Error::Timeout(url) => {
+            errorln!("{}", tr!(Msg::ErrTimeout, url));
+        }
+        Error::Dns(host) => {
+            errorln!("{}", tr!(Msg::ErrDns, host));
+        }
+        Error::Tls(url, detail) => {
+            errorln!("{}", tr!(Msg::ErrTls, url, detail));
+        }
+        Error::HttpStatus(status, url) => {
+            errorln!("{}", tr!(Msg::ErrHttpStatus, status, url));
+        }
+        Error::InvalidProxy(url, detail) => {
+            errorln!("{}", tr!(Msg::ErrInvalidProxy, url, detail));
+        }
+        Error::Certificate(path, detail) => {
+            errorln!("{}", tr!(Msg::ErrCertificate, path, detail));
         }
         Error::CookieMissing => {
-            errorln!("Cookieが見つかりません。再度ログインをして下さい");
+            errorln!("{}", Msg::ErrCookieMissing);
         }
         Error::HeaderMissing(err) => {
-            errorln!(
-                "ログイン処理に失敗しました。もう一度やり直すか、開発者にお問い合わせ下さい。"
-            );
-            errorln!("Error: {}", err);
+            errorln!("{}", Msg::ErrLoginProcess);
+            errorln!("{}", tr!(Msg::ErrDetail, err));
         }
         Error::TokenNotFound(err) => {
-            errorln!(
-                "ログイン処理に失敗しました。もう一度やり直すか、開発者にお問い合わせ下さい。"
-            );
-            errorln!("Error: {}", err);
+            errorln!("{}", Msg::ErrLoginProcess);
+            errorln!("{}", tr!(Msg::ErrDetail, err));
         }
         Error::LoginFailed => {
-            errorln!("ログインに失敗しました。メールアドレスやパスワードをご確認下さい。");
+            errorln!("{}", Msg::ErrLoginFailed);
         }
         Error::MalformedCookie(s) => {
-            errorln!("Cookieの形式が不正です: {}", s);
-        }
-        Error::NoCookie => {
-            errorln!("ログイン状態の確認に失敗しました。もう一度ログインして下さい。");
+            errorln!("{}", tr!(Msg::ErrMalformedCookie, s));
         }
         Error::CookieNotUtf8 => {
-            errorln!("CookieファイルがUTF-8として正しく読み取れませんでした。");
+            errorln!("{}", Msg::ErrCookieNotUtf8);
         }
         Error::UrlIncorrectFormat => {
-            errorln!("URLの形式が正しくありません。正しい形式で入力して下さい。");
-            errorln!("Example: https://recursionist.io/dashboard/problems/1");
+            errorln!("{}", Msg::ErrUrlFormat);
+            errorln!("{}", Msg::ErrExampleUrl);
         }
-        Error::AuthenticationError => {
-            errorln!("認証に失敗しました。ログインし直して下さい。");
+        Error::Unauthenticated => {
+            errorln!("{}", Msg::ErrUnauthenticated);
         }
         Error::CookieNotFound => {
-            errorln!("Cookieファイルが見つかりませんでした。");
+            errorln!("{}", Msg::ErrCookieNotFound);
+        }
+        Error::Config(err) => {
+            errorln!("{}", tr!(Msg::ErrConfig, err));
+        }
+        Error::ConfigKeyNotFound(key) => {
+            errorln!("{}", tr!(Msg::ErrConfigKeyNotFound, key));
+        }
+        Error::NoJudgeCommand => {
+            errorln!("{}", Msg::ErrNoJudgeCommand);
+        }
+        Error::BuildFailed(command) => {
+            errorln!("{}", tr!(Msg::ErrBuildFailed, command));
+        }
+        Error::UnknownProfile(name) => {
+            errorln!("{}", tr!(Msg::ErrUnknownProfile, name));
+        }
+        Error::TemplateNotFound(name) => {
+            errorln!("{}", tr!(Msg::ErrTemplateNotFound, name));
+        }
+        Error::TemplateExists(name) => {
+            errorln!("{}", tr!(Msg::ErrTemplateExists, name));
+        }
+        Error::InvalidTemplateName(name) => {
+            errorln!("{}", tr!(Msg::ErrInvalidTemplateName, name));
+        }
+        Error::InvalidRenderedName(name) => {
+            errorln!("{}", tr!(Msg::ErrInvalidRenderedName, name));
+        }
+        Error::StubUnsupported(name) => {
+            errorln!("{}", tr!(Msg::ErrStubUnsupported, name));
+        }
+        Error::FunctionUnsupported(name) => {
+            errorln!("{}", tr!(Msg::ErrFunctionUnsupported, name));
+        }
+        Error::NoFunctionName => {
+            errorln!("{}", Msg::ErrNoFunctionName);
+        }
+        Error::NoProblemId => {
+            errorln!("{}", Msg::ErrNoProblemId);
+        }
+        Error::FunctionNotFound(name) => {
+            errorln!("{}", tr!(Msg::ErrFunctionNotFound, name));
+        }
+        Error::Literal(detail) => {
+            errorln!("{}", tr!(Msg::ErrLiteral, detail));
+        }
+        Error::InvalidTarget(target) => {
+            errorln!("{}", tr!(Msg::ErrInvalidTarget, target));
+        }
+        Error::DownloadsFailed(count) => {
+            errorln!("{}", tr!(Msg::ErrDownloadsFailed, count));
+        }
+        Error::NoCatalogCache => {
+            errorln!("{}", Msg::ErrNoCatalogCache);
+        }
+        Error::EmptyProblemIndex(url) => {
+            errorln!("{}", tr!(Msg::ErrEmptyProblemIndex, url));
+        }
+        Error::NotCached(url) => {
+            errorln!("{}", tr!(Msg::ErrNotCached, url));
+        }
+        Error::UnexpectedResponse(detail) => {
+            errorln!("{}", tr!(Msg::ErrUnexpectedResponse, detail));
+        }
+        Error::VerdictTimeout => {
+            errorln!("{}", Msg::ErrVerdictTimeout);
+        }
+        Error::NoSourceFile => {
+            errorln!("{}", Msg::ErrNoSourceFile);
+        }
+        Error::NoLanguage(file) => {
+            errorln!("{}", tr!(Msg::ErrNoLanguage, file));
         }
     }
 }
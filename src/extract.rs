@@ -0,0 +1,370 @@
+use log::{debug, warn};
+use regex::Regex;
+use std::{env, path::Path};
+
+use crate::{error::Error, file::read_file, problem::Problem};
+
+/// Marks the start of the code to submit, e.g. `# rlr:begin` or `// rlr:begin`.
+const BEGIN_MARKER: &str = "rlr:begin";
+/// Marks the end of the code to submit.
+const END_MARKER: &str = "rlr:end";
+
+/// Words that can come right before a call, so a name after them is not a
+/// definition with a return type (`return f(a)`).
+const NOT_TYPES: &[&str] = &[
+    "and", "await", "case", "delete", "echo", "else", "in", "new", "not", "of", "or", "print",
+    "return", "throw", "typeof", "yield",
+];
+
+/// How a function body is delimited when the source has no markers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockRule {
+    /// The body is indented under the definition (Python).
+    Indent,
+    /// The body ends with an `end` at the indentation of the definition (Ruby).
+    End,
+    /// The body is enclosed in braces (C-like languages).
+    Brace,
+}
+
+impl BlockRule {
+    fn from_extension(extension: &str) -> Self {
+        match extension {
+            "py" => BlockRule::Indent,
+            "rb" => BlockRule::End,
+            _ => BlockRule::Brace,
+        }
+    }
+
+    fn definition(&self, name: &str) -> Result<Regex, Error> {
+        let name = regex::escape(name);
+        let pattern = match self {
+            BlockRule::Indent => format!(r"^\s*(async\s+)?def\s+{}\s*\(", name),
+            BlockRule::End => format!(r"^\s*def\s+(self\.)?{}\b", name),
+            BlockRule::Brace => format!(
+                r"\b(?:fn|fun|func|function)\s+{0}\b|\b(?:const|let|var)\s+{0}\s*=|(?P<typed>(?:^\s*|(?P<before>\w+|[>\])*&])\s+)[*&]*{0}\s*\()",
+                name
+            ),
+        };
+        Regex::new(&pattern)
+            .map_err(|_| Error::Internal("Regex compile error in definition".to_string()))
+    }
+
+    /// The line `name` is defined on. In C-like code the name follows a
+    /// keyword (`fn f`, `function f`, `const f =`), or a return type and then
+    /// the parameters and a body, so calls and prototypes are skipped.
+    fn find_definition(&self, lines: &[&str], name: &str) -> Result<Option<usize>, Error> {
+        let definition = self.definition(name)?;
+
+        Ok(lines.iter().enumerate().position(|(i, line)| {
+            definition
+                .captures_iter(line)
+                .any(|caps| match caps.name("typed") {
+                    None => true,
+                    Some(typed) => {
+                        !caps
+                            .name("before")
+                            .is_some_and(|word| NOT_TYPES.contains(&word.as_str()))
+                            && has_body(lines, i, typed.end())
+                    }
+                })
+        }))
+    }
+}
+
+/// Prints the part of `file` to paste into the site's editor: the code between
+/// the marker comments, or the function found by the rules of the language.
+pub fn extract(file: &str, function: Option<String>) -> Result<(), Error> {
+    println!("{}", extract_code(file, function)?);
+    Ok(())
+}
+
+/// The code `extract` prints, which is also what `submit` sends.
+pub fn extract_code(file: &str, function: Option<String>) -> Result<String, Error> {
+    let source = read_file(file)?;
+    let expected = match function {
+        Some(name) => Some(name),
+        None => Problem::load(&env::current_dir()?)?.and_then(|p| p.function_name),
+    };
+
+    let code = match extract_marked(&source) {
+        Some(code) => code,
+        None => {
+            let name = expected.as_deref().ok_or(Error::NoFunctionName)?;
+            let extension = Path::new(file)
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or_default();
+            extract_function(&source, name, BlockRule::from_extension(extension))?
+                .ok_or(Error::FunctionNotFound(name.to_string()))?
+        }
+    };
+
+    if let Some(name) = &expected
+        && !defines_function(&code, name)?
+    {
+        warn!(
+            "the extracted code does not define {}, the function of this problem",
+            name
+        );
+    }
+
+    Ok(code)
+}
+
+/// Joins every block between a begin and an end marker.
+fn extract_marked(source: &str) -> Option<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+
+    for line in source.lines() {
+        if line.contains(BEGIN_MARKER) {
+            current = Some(Vec::new());
+        } else if line.contains(END_MARKER) {
+            if let Some(lines) = current.take() {
+                blocks.push(dedent(&lines));
+            }
+        } else if let Some(lines) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+
+    debug!("{} marked blocks found", blocks.len());
+    (!blocks.is_empty()).then(|| blocks.join("\n\n"))
+}
+
+fn extract_function(source: &str, name: &str, rule: BlockRule) -> Result<Option<String>, Error> {
+    let lines = source.lines().collect::<Vec<_>>();
+
+    let Some(start) = rule.find_definition(&lines, name)? else {
+        return Ok(None);
+    };
+    let indent = indent_width(lines[start]);
+
+    let end = match rule {
+        BlockRule::Indent => {
+            let mut end = start;
+            for (i, line) in lines.iter().enumerate().skip(start + 1) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if indent_width(line) <= indent {
+                    break;
+                }
+                end = i;
+            }
+            Some(end)
+        }
+        BlockRule::End => lines
+            .iter()
+            .enumerate()
+            .skip(start + 1)
+            .find(|(_, line)| line.trim() == "end" && indent_width(line) == indent)
+            .map(|(i, _)| i),
+        BlockRule::Brace => {
+            let mut depth = 0;
+            let mut opened = false;
+            let mut end = None;
+            for (i, line) in lines.iter().enumerate().skip(start) {
+                for c in strip_strings(line).chars() {
+                    match c {
+                        '{' => {
+                            depth += 1;
+                            opened = true;
+                        }
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                }
+                // a one-line arrow function has no braces at all
+                let one_liner = !opened && strip_strings(line).trim_end().ends_with(';');
+                if (opened && depth <= 0) || one_liner {
+                    end = Some(i);
+                    break;
+                }
+            }
+            end
+        }
+    };
+
+    Ok(end.map(|end| dedent(&lines[start..=end])))
+}
+
+fn defines_function(code: &str, name: &str) -> Result<bool, Error> {
+    let re = Regex::new(&format!(r"\b{}\b", regex::escape(name)))
+        .map_err(|_| Error::Internal("Regex compile error in defines_function".to_string()))?;
+    Ok(re.is_match(code))
+}
+
+/// Whether the parameters opened before `lines[line][offset..]` are followed
+/// by a `{`, e.g. `long f(long a) const {`, rather than a `;` or another call.
+fn has_body(lines: &[&str], line: usize, offset: usize) -> bool {
+    let rest = std::iter::once(&lines[line][offset..]).chain(lines[line + 1..].iter().copied());
+    let mut depth = 1;
+
+    for c in rest.flat_map(|line| strip_strings(line).chars().collect::<Vec<_>>()) {
+        match c {
+            '(' if depth > 0 => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            '{' if depth == 0 => return true,
+            ';' | '(' | ')' if depth == 0 => return false,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Drops string and char literals and line comments, so that the braces in
+/// them are not counted. A `'` starts a char literal only when it is closed
+/// right after (`'{'`, `'\n'`), so Rust lifetimes are kept.
+fn strip_strings(line: &str) -> String {
+    let chars = line.chars().collect::<Vec<_>>();
+    let mut result = String::new();
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '"' | '`' => quote = Some(c),
+                '\'' if chars.get(i + 2) == Some(&'\'') || chars.get(i + 1) == Some(&'\\') => {
+                    quote = Some(c)
+                }
+                '/' if chars.get(i + 1) == Some(&'/') => break,
+                _ => result.push(c),
+            },
+        }
+    }
+    result
+}
+
+fn indent_width(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Removes the indentation common to every non-blank line.
+fn dedent(lines: &[&str]) -> String {
+    let width = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| indent_width(line))
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|line| line.get(width..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_marked() {
+        let source =
+            "import sys\n\n# rlr:begin\ndef f(a):\n    return a\n# rlr:end\n\nprint(f(1))\n";
+
+        assert_eq!(
+            extract_marked(source),
+            Some("def f(a):\n    return a".to_string())
+        );
+        assert_eq!(extract_marked("def f(a):\n    return a\n"), None);
+    }
+
+    #[test]
+    fn test_extract_marked_dedents() {
+        let source = "class Main {\n    // rlr:begin\n    static long f() {\n        return 0;\n    }\n    // rlr:end\n}\n";
+
+        assert_eq!(
+            extract_marked(source).unwrap(),
+            "static long f() {\n    return 0;\n}"
+        );
+    }
+
+    #[test]
+    fn test_extract_python_function() {
+        let source = "import sys\n\ndef f(a):\n    if a:\n\n        return 1\n    return 0\n\nprint(f(sys.argv))\n";
+
+        let actual = extract_function(source, "f", BlockRule::Indent).unwrap();
+
+        assert_eq!(
+            actual.as_deref(),
+            Some("def f(a):\n    if a:\n\n        return 1\n    return 0")
+        );
+    }
+
+    #[test]
+    fn test_extract_brace_function() {
+        let source = "const fs = require(\"fs\");\n\nconst f = (a) => {\n  if (a) { return \"}\"; }\n  return 0;\n};\n\nconsole.log(f(1));\n";
+
+        let actual = extract_function(source, "f", BlockRule::Brace).unwrap();
+
+        assert_eq!(
+            actual.as_deref(),
+            Some("const f = (a) => {\n  if (a) { return \"}\"; }\n  return 0;\n};")
+        );
+    }
+
+    #[test]
+    fn test_extract_brace_function_after_prototype_and_call() {
+        let source = "#include <iostream>\n\nlong long f(long long a);\n\nint main() {\n    std::cout << f(1) << std::endl;\n    return f(2);\n}\n\nlong long f(long long a)\n{\n    return a;\n}\n";
+
+        let actual = extract_function(source, "f", BlockRule::Brace).unwrap();
+
+        assert_eq!(
+            actual.as_deref(),
+            Some("long long f(long long a)\n{\n    return a;\n}")
+        );
+
+        let source = "fn main() {\n    let x = f(1);\n    println!(\"{}\", x);\n}\n\nfn f(a: i64) -> i64 {\n    a\n}\n";
+
+        let actual = extract_function(source, "f", BlockRule::Brace).unwrap();
+
+        assert_eq!(actual.as_deref(), Some("fn f(a: i64) -> i64 {\n    a\n}"));
+    }
+
+    #[test]
+    fn test_extract_ruby_function() {
+        let source = "def f(a)\n  if a\n    1\n  end\nend\n\nputs f(1)\n";
+
+        let actual = extract_function(source, "f", BlockRule::End).unwrap();
+
+        assert_eq!(
+            actual.as_deref(),
+            Some("def f(a)\n  if a\n    1\n  end\nend")
+        );
+    }
+
+    #[test]
+    fn test_strip_strings() {
+        assert_eq!(
+            strip_strings(r#"if (c == '{') { s = "}"; } // }"#),
+            "if (c == ) { s = ; } "
+        );
+        assert_eq!(
+            strip_strings("fn f<'a>(x: &'a str) -> &'a str {"),
+            "fn f<'a>(x: &'a str) -> &'a str {"
+        );
+    }
+
+    #[test]
+    fn test_function_not_found() {
+        let actual = extract_function("def g():\n    pass\n", "f", BlockRule::Indent).unwrap();
+
+        assert_eq!(actual, None);
+    }
+}
@@ -19,9 +19,20 @@ pub fn cookie_path() -> Result<(), Error> {
 }
 
 pub fn get_cookie_path() -> Result<std::path::PathBuf, Error> {
+    Ok(get_config_dir()?.join("cookie.jar"))
+}
+
+pub fn get_config_dir() -> Result<std::path::PathBuf, Error> {
+    if let Some(project_dir) = ProjectDirs::from("Recursion", "tool", "rlr") {
+        Ok(project_dir.config_dir().to_path_buf())
+    } else {
+        Err(Error::CookiePathUnvaliable)
+    }
+}
+
+pub fn get_cache_dir() -> Result<std::path::PathBuf, Error> {
     if let Some(project_dir) = ProjectDirs::from("Recursion", "tool", "rlr") {
-        let config_dir = project_dir.config_dir();
-        Ok(config_dir.join("cookie.jar"))
+        Ok(project_dir.cache_dir().to_path_buf())
     } else {
         Err(Error::CookiePathUnvaliable)
     }
@@ -0,0 +1,136 @@
+use log::debug;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::Config,
+    error::Error,
+    language::{ResolvedCommand, detect_profile, find_profile},
+    layout::InputConversion,
+    problem::Problem,
+};
+
+/// Harness for `judge --function`: it loads the user's source file, calls the
+/// function with the arguments of a case read from stdin and prints the return
+/// value in the RecursionCS format.
+///
+/// The file is removed when the harness is dropped.
+pub struct Harness {
+    path: PathBuf,
+    /// How the `.in` files are turned into the JSON lines the harness reads.
+    pub input: InputConversion,
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Harness source and file extension of each supported language profile.
+fn harness_source(profile_name: &str) -> Option<(&'static str, &'static str)> {
+    match profile_name {
+        "python" => Some((include_str!("harness/python.py"), "py")),
+        "javascript" => Some((include_str!("harness/javascript.js"), "js")),
+        "ruby" => Some((include_str!("harness/ruby.rb"), "rb")),
+        "php" => Some((include_str!("harness/php.php"), "php")),
+        _ => None,
+    }
+}
+
+/// Builds the command that runs the harness through the language profile, so
+/// `{source}` in the profile's run command becomes the harness followed by the
+/// user's source file and the function name.
+///
+/// The function name is `function`, or the one saved in `problem.toml`.
+pub fn function_command(
+    function: Option<String>,
+    profile: Option<String>,
+    config: &Config,
+) -> Result<(ResolvedCommand, Harness), Error> {
+    let cwd = env::current_dir()?;
+    let problem = Problem::load(&cwd)?.unwrap_or_default();
+
+    let function = function
+        .or(problem.function_name)
+        .ok_or(Error::NoFunctionName)?;
+
+    let (name, profile, source) = match profile.or(config.judge.profile.clone()) {
+        Some(name) => {
+            let profile = find_profile(&name, config)?;
+            let source = profile.source.clone();
+            (name, profile, source)
+        }
+        None => detect_profile(&cwd, config).ok_or(Error::NoJudgeCommand)?,
+    };
+
+    let (code, extension) =
+        harness_source(&name).ok_or(Error::FunctionUnsupported(name.clone()))?;
+
+    let path = env::temp_dir().join(format!("rlr-harness-{}.{}", std::process::id(), extension));
+    fs::write(&path, code)?;
+    debug!("write harness: {:?}", path);
+
+    let arguments = format!(
+        "{} {} {}",
+        shell_quote(&path),
+        shell_quote(Path::new(&source)),
+        function
+    );
+    let command = ResolvedCommand {
+        run: profile.run.replace("{source}", &arguments),
+        build: None,
+        origin: format!("profile {}, function {}", name, function),
+    };
+
+    let input = InputConversion {
+        from: problem.input_layout,
+        arg_types: problem.arg_types,
+    };
+    Ok((command, Harness { path, input }))
+}
+
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_command() {
+        let (command, harness) = function_command(
+            Some("sum".to_string()),
+            Some("python".to_string()),
+            &Config::default(),
+        )
+        .unwrap();
+
+        assert!(command.run.starts_with("python3 '"));
+        assert!(command.run.ends_with(".py' 'main.py' sum"));
+        assert!(harness.path.is_file());
+
+        let path = harness.path.clone();
+        drop(harness);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_function_command_unsupported_language() {
+        let result = function_command(
+            Some("sum".to_string()),
+            Some("rust".to_string()),
+            &Config::default(),
+        );
+
+        assert!(matches!(result, Err(Error::FunctionUnsupported(_))));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote(Path::new("it's.py")), r"'it'\''s.py'");
+    }
+}
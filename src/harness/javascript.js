@@ -0,0 +1,35 @@
+const fs = require("fs");
+
+function parse(token) {
+  try {
+    return JSON.parse(token);
+  } catch (e) {
+    return token;
+  }
+}
+
+function toText(value) {
+  if (Array.isArray(value)) {
+    return "[" + value.map(toText).join(",") + "]";
+  }
+  if (typeof value === "string") {
+    return JSON.stringify(value);
+  }
+  return String(value);
+}
+
+const [source, name] = process.argv.slice(2);
+// read the input first, the source may read stdin when it is loaded;
+// rlr gives one argument per line as JSON
+const args = fs
+  .readFileSync(0, "utf8")
+  .split(/\r?\n/)
+  .filter((line) => line !== "")
+  .map(parse);
+const code = fs.readFileSync(source, "utf8");
+// the source may declare the function with function, const or let
+const solution = new Function("require", "module", `${code}\nreturn ${name};`)(
+  require,
+  { exports: {} },
+);
+console.log(toText(solution(...args)));
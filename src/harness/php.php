@@ -0,0 +1,31 @@
+<?php
+
+function __rlr_parse($token)
+{
+    $value = json_decode($token, true);
+    return json_last_error() === JSON_ERROR_NONE ? $value : $token;
+}
+
+function __rlr_to_text($value)
+{
+    if (is_bool($value)) {
+        return $value ? "true" : "false";
+    }
+    if (is_null($value)) {
+        return "null";
+    }
+    if (is_string($value)) {
+        return json_encode($value, JSON_UNESCAPED_UNICODE | JSON_UNESCAPED_SLASHES);
+    }
+    if (is_array($value)) {
+        return "[" . implode(",", array_map("__rlr_to_text", $value)) . "]";
+    }
+    return strval($value);
+}
+
+[$source, $name] = array_slice($argv, 1);
+// rlr gives one argument per line as JSON
+$lines = preg_split("/\r?\n/", stream_get_contents(STDIN), -1, PREG_SPLIT_NO_EMPTY);
+$args = array_map("__rlr_parse", $lines);
+require $source;
+echo __rlr_to_text(call_user_func_array($name, $args)), PHP_EOL;
@@ -0,0 +1,38 @@
+import importlib.util
+import json
+import sys
+
+
+def parse(token):
+    try:
+        return json.loads(token)
+    except ValueError:
+        return token
+
+
+def to_text(value):
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    if value is None:
+        return "null"
+    if isinstance(value, str):
+        return json.dumps(value, ensure_ascii=False)
+    if isinstance(value, (list, tuple)):
+        return "[" + ",".join(to_text(v) for v in value) + "]"
+    return str(value)
+
+
+def main():
+    source, name = sys.argv[1], sys.argv[2]
+    # read the input first, the source may read stdin when it is loaded;
+    # rlr gives one argument per line as JSON
+    args = [parse(line) for line in sys.stdin.read().splitlines() if line]
+
+    spec = importlib.util.spec_from_file_location("solution", source)
+    solution = importlib.util.module_from_spec(spec)
+    spec.loader.exec_module(solution)
+
+    print(to_text(getattr(solution, name)(*args)))
+
+
+main()
@@ -0,0 +1,21 @@
+require "json"
+
+def __rlr_parse(token)
+  JSON.parse(token)
+rescue JSON::ParserError
+  token
+end
+
+def __rlr_to_text(value)
+  return "[" + value.map { |v| __rlr_to_text(v) }.join(",") + "]" if value.is_a?(Array)
+  return value.to_json if value.is_a?(String)
+  return "null" if value.nil?
+
+  value.to_s
+end
+
+source, name = ARGV
+# rlr gives one argument per line as JSON
+args = $stdin.read.lines(chomp: true).reject(&:empty?).map { |line| __rlr_parse(line) }
+load File.expand_path(source)
+puts __rlr_to_text(send(name, *args))
@@ -0,0 +1,814 @@
+use clap::{Command, ValueEnum};
+use once_cell::sync::OnceCell;
+use std::fmt;
+
+static LANG: OnceCell<Lang> = OnceCell::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Lang {
+    Ja,
+    En,
+}
+
+/// Decides the display language from `--lang`, then `RLR_LANG`, then `LANG`.
+/// Japanese is the default when none of them names a supported language.
+pub fn init_lang(cli_lang: Option<Lang>) -> Lang {
+    let lang = cli_lang
+        .or_else(|| std::env::var("RLR_LANG").ok().and_then(|v| parse_lang(&v)))
+        .or_else(|| std::env::var("LANG").ok().and_then(|v| parse_lang(&v)))
+        .unwrap_or(Lang::Ja);
+
+    *LANG.get_or_init(|| lang)
+}
+
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or(Lang::Ja)
+}
+
+fn parse_lang(value: &str) -> Option<Lang> {
+    let value = value.to_ascii_lowercase();
+    if value.starts_with("ja") {
+        Some(Lang::Ja)
+    } else if value.starts_with("en") {
+        Some(Lang::En)
+    } else {
+        None
+    }
+}
+
+/// Finds `--lang <LANG>` / `--lang=<LANG>` before clap runs, so that the help
+/// text itself can be localized.
+pub fn lang_from_args<I: IntoIterator<Item = String>>(args: I) -> Option<Lang> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = if arg == "--lang" {
+            args.next()
+        } else {
+            arg.strip_prefix("--lang=").map(|v| v.to_string())
+        };
+
+        if let Some(value) = value {
+            return Lang::from_str(&value, true).ok();
+        }
+    }
+    None
+}
+
+/// Replaces each `{}` in `template` with the next argument.
+pub fn format_msg(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut result = String::new();
+    let mut args = args.iter();
+    let mut rest = template;
+
+    while let Some(pos) = rest.find("{}") {
+        result.push_str(&rest[..pos]);
+        match args.next() {
+            Some(arg) => result.push_str(&arg.to_string()),
+            None => result.push_str("{}"),
+        }
+        rest = &rest[pos + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+macro_rules! tr {
+    ($msg:expr) => {
+        $msg.text().to_string()
+    };
+    ($msg:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format_msg($msg.text(), &[$(&$arg),+])
+    };
+}
+pub(crate) use tr;
+
+macro_rules! catalog {
+    ($($key:ident => ($ja:expr, $en:expr),)*) => {
+        #[derive(Debug, Clone, Copy)]
+        pub enum Msg {
+            $($key,)*
+        }
+
+        impl Msg {
+            pub fn text(self) -> &'static str {
+                self.text_in(lang())
+            }
+
+            fn text_in(self, lang: Lang) -> &'static str {
+                match self {
+                    $(Msg::$key => match lang {
+                        Lang::Ja => $ja,
+                        Lang::En => $en,
+                    },)*
+                }
+            }
+        }
+    };
+}
+
+impl fmt::Display for Msg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+catalog! {
+    // prompts
+    PromptEmail => ("Emailアドレス: ", "Email address: "),
+    PromptPassword => ("Password: ", "Password: "),
+    PasswordInputFailed => ("パスワードの入力処理に失敗しました", "Failed to read the password"),
+    InputFailed => ("入力に失敗しました: {}", "Failed to read input: {}"),
+    EmailFormat => ("Emailの形式で入力して下さい。", "Please enter a valid email address."),
+    LoginSuccess => ("ログインに成功しました。", "Login success."),
+    LoginFailure => ("ログインに失敗しました。", "Login failed."),
+    SavedTo => ("保存しました: {}", "Saved to : {}"),
+
+    // download
+    AlreadyDownloaded => (
+        "問題 {} はダウンロード済みのためスキップします: {}",
+        "Skipping problem {}, already downloaded: {}"
+    ),
+    DownloadProgress => ("問題 {} ({}/{})", "Problem {} ({}/{})"),
+    ColumnProblem => ("問題", "id"),
+    ColumnResult => ("結果", "result"),
+    ColumnDetail => ("詳細", "detail"),
+
+    // submit
+    Submitting => ("問題 {} に {} を提出します ({})", "Submitting to problem {}: {} ({})"),
+    Submitted => ("提出しました: {}", "Submitted: {}"),
+    WaitingVerdict => ("判定を待っています...", "Waiting for the verdict..."),
+
+    // list
+    ProblemsListed => ("{} 件の問題 (取得日時: {})", "{} problems (fetched at {})"),
+    CatalogFromCache => ("キャッシュした問題一覧を表示します", "Showing the cached problem index"),
+
+    // judge
+    CasesFound => ("{} 件のテストケースが見つかりました", "{} cases found"),
+    JudgeStart => ("ジャッジを開始します", "judge start"),
+    JudgeEnd => ("ジャッジが終了しました", "end judge"),
+    CaseTime => ("実行時間: {} 秒", "time: {} sec"),
+    Slowest => ("最長実行時間: {} 秒 ({})", "slowest: {} sec (for {})"),
+    TimeLimitExceeded => (
+        "プログラムの実行時間が{}秒を超えました。",
+        "The program ran for more than {} seconds."
+    ),
+    Accepted => ("AC (正解)", "AC (Accepted)"),
+    WrongAnswer => ("WA (不正解)", "WA (Wrong Answer)"),
+    RuntimeError => ("RE (実行時エラー)", "RE (Runtime Error)"),
+    TimeLimit => ("TLE (実行時間超過)", "TLE (Time Limit Exceeded)"),
+    InputHeading => ("入力:", "input:"),
+    OutputHeading => ("出力:", "output:"),
+    ExpectedHeading => ("期待値:", "expected:"),
+    StderrHeading => ("標準エラー出力:", "stderr:"),
+    StdoutHeading => ("標準出力:", "stdout:"),
+    RunInputFrom => ("入力: {}", "input: {}"),
+    RunExitStatus => ("終了ステータス: {}", "exit status: {}"),
+    RunMemory => ("メモリ使用量: {} KB", "memory: {} KB"),
+    Build => ("ビルド: {}", "build: {}"),
+    CommandFrom => ("実行コマンド: {} ({})", "command: {} ({})"),
+    DiffLegend => ("差分 (-: 期待値, +: 出力):", "diff (-: expected, +: output):"),
+    ColumnCase => ("ケース", "case"),
+    ColumnVerdict => ("判定", "verdict"),
+    ColumnTime => ("実行時間 (秒)", "time (sec)"),
+    ColumnMemory => ("メモリ (KB)", "memory (KB)"),
+    ColumnReason => ("理由", "reason"),
+    ReasonWrongAnswer => ("期待値 {}、出力 {}", "expected {}, got {}"),
+    ReasonTimeLimit => ("{} 秒を超えました", "exceeded {} sec"),
+    ExitCode => ("終了コード {}", "exit code {}"),
+    ExitSignal => ("シグナル {}", "signal {}"),
+    ExitTerminated => ("強制終了", "terminated"),
+
+    // template
+    TemplateUsed => ("テンプレート: {}", "template: {}"),
+    TemplateAdded => ("テンプレートを追加しました: {} ({})", "Added template: {} ({})"),
+    TemplateRemoved => ("テンプレートを削除しました: {} ({})", "Removed template: {} ({})"),
+    NoTemplates => ("テンプレートがありません", "No templates"),
+
+    // errors
+    ErrSelector => (
+        "テストケースの取得に失敗しました。ログインに失敗しているか、指定したURLが正しくない可能性があります: {}",
+        "Failed to get the test cases. You may not be logged in, or the URL may be wrong: {}"
+    ),
+    ErrIo => ("I/O処理でエラーが発生しました: {}", "An I/O error occurred: {}"),
+    ErrInternal => ("内部エラーが発生しました。", "An internal error occurred."),
+    ErrCookiePath => ("Cookieファイルのパス取得に失敗しました。", "Failed to get the cookie file path."),
+    ErrNetwork => ("ネットワークエラーが発生しました。{}", "A network error occurred. {}"),
+    ErrTimeout => (
+        "サイトから時間内に応答がありませんでした: {}",
+        "The site did not answer in time: {}"
+    ),
+    ErrDns => (
+        "ホスト名を解決できませんでした: {} (ネットワーク接続を確認して下さい)",
+        "Could not resolve the host name: {} (check the network connection)"
+    ),
+    ErrTls => (
+        "TLS接続に失敗しました: {} ({})",
+        "The TLS connection failed: {} ({})"
+    ),
+    ErrHttpStatus => (
+        "サイトがHTTP {} を返しました: {}",
+        "The site returned HTTP {}: {}"
+    ),
+    ErrInvalidProxy => (
+        "プロキシの指定が正しくありません: {} ({})",
+        "Invalid proxy: {} ({})"
+    ),
+    ErrCertificate => (
+        "証明書を読み込めません: {} ({})",
+        "Failed to read the certificate: {} ({})"
+    ),
+    ErrCookieMissing => (
+        "Cookieが見つかりません。再度ログインをして下さい",
+        "No cookie was found. Please log in again."
+    ),
+    ErrLoginProcess => (
+        "ログイン処理に失敗しました。もう一度やり直すか、開発者にお問い合わせ下さい。",
+        "The login process failed. Please try again, or contact the developer."
+    ),
+    ErrDetail => ("Error: {}", "Error: {}"),
+    ErrLoginFailed => (
+        "ログインに失敗しました。メールアドレスやパスワードをご確認下さい。",
+        "Login failed. Please check your email address and password."
+    ),
+    ErrMalformedCookie => ("Cookieの形式が不正です: {}", "The cookie is malformed: {}"),
+    ErrCookieNotUtf8 => (
+        "CookieファイルがUTF-8として正しく読み取れませんでした。",
+        "The cookie file could not be read as UTF-8."
+    ),
+    ErrUrlFormat => (
+        "URLの形式が正しくありません。正しい形式で入力して下さい。",
+        "The URL format is incorrect. Please enter it in the correct format."
+    ),
+    ErrExampleUrl => (
+        "Example: https://recursionist.io/dashboard/problems/1",
+        "Example: https://recursionist.io/dashboard/problems/1"
+    ),
+    ErrUnauthenticated => ("認証に失敗しました。ログインし直して下さい。", "Authentication failed. Please log in again."),
+    ErrCookieNotFound => ("Cookieファイルが見つかりませんでした。", "The cookie file was not found."),
+    ErrConfig => ("設定ファイルの内容が正しくありません: {}", "The config file is invalid: {}"),
+    ErrConfigKeyNotFound => ("設定項目が見つかりません: {}", "No such config key: {}"),
+    ErrNoJudgeCommand => (
+        "実行するコマンドが見つかりません。引数で指定するか、rlr.tomlのjudge.commandを設定するか、main.pyなどのソースファイルを配置して下さい。",
+        "No command to run. Pass it as an argument, set judge.command in rlr.toml, or add a source file such as main.py."
+    ),
+    ErrUnknownProfile => ("不明な言語プロファイルです: {}", "Unknown language profile: {}"),
+    ErrBuildFailed => ("ビルドに失敗しました: {}", "The build failed: {}"),
+    ErrTemplateNotFound => ("テンプレートが見つかりません: {}", "No such template: {}"),
+    ErrTemplateExists => ("テンプレートは既に存在します: {}", "The template already exists: {}"),
+    ErrInvalidTemplateName => (
+        "テンプレート名にはディレクトリ名を1つだけ指定して下さい(/、.、..は使えません): {}",
+        "A template name must be a single directory name, without /, . or ..: {}"
+    ),
+    ErrInvalidRenderedName => (
+        "テンプレートのファイル名が問題ディレクトリの外を指しています: {}",
+        "A file name of the template points outside the problem directory: {}"
+    ),
+    ErrStubUnsupported => (
+        "この言語のスタブは生成できません: {} (python、javascript、ruby、rust、cpp、javaに対応しています)",
+        "Cannot generate a stub for: {} (python, javascript, ruby, rust, cpp and java are supported)"
+    ),
+    ErrFunctionUnsupported => (
+        "この言語は関数モードに対応していません: {} (python、javascript、ruby、phpに対応しています)",
+        "Function mode does not support: {} (python, javascript, ruby and php are supported)"
+    ),
+    ErrFunctionNotFound => (
+        "ソースファイルに関数が見つかりません: {}",
+        "The function was not found in the source file: {}"
+    ),
+    ErrLiteral => (
+        "テストケースの値を解析できませんでした: {}",
+        "Failed to parse a value in the test cases: {}"
+    ),
+    ErrNoFunctionName => (
+        "関数名が分かりません。--function <関数名>で指定するか、問題をダウンロードし直して下さい。",
+        "The function name is unknown. Pass it with --function <NAME> or download the problem again."
+    ),
+    ErrNoProblemId => (
+        "ページやproblem.tomlから問題番号が分かりませんでした。--id <問題番号>で指定して下さい。",
+        "The problem number could not be found in the page or problem.toml. Pass it with --id <N>."
+    ),
+    ErrInvalidTarget => (
+        "問題の指定が正しくありません: {} (例: 1、1..50、1,3,5)",
+        "Invalid problem: {} (e.g. 1, 1..50, 1,3,5)"
+    ),
+    ErrUnexpectedResponse => ("サイトから想定外の応答がありました: {}", "Unexpected response from the site: {}"),
+    ErrVerdictTimeout => (
+        "判定結果が時間内に届きませんでした。サイトで結果を確認して下さい。",
+        "The verdict did not arrive in time. Please check the result on the site."
+    ),
+    ErrNoSourceFile => (
+        "提出するソースファイルが見つかりません。ファイルを指定して下さい。",
+        "No source file to submit was found. Please pass the file."
+    ),
+    ErrNoLanguage => (
+        "言語が分かりません: {} (--languageで指定して下さい)",
+        "Unknown language of: {} (pass it with --language)"
+    ),
+    ErrEmptyProblemIndex => (
+        "問題一覧に問題が見つかりませんでした。ログインやサイトの設定を確認して下さい: {}",
+        "No problem was found in the problem index. Check the login and the site settings: {}"
+    ),
+    ErrNoCatalogCache => (
+        "問題一覧のキャッシュがありません。--offlineを付けずに実行して下さい。",
+        "The problem index is not cached yet. Run it once without --offline."
+    ),
+    ErrNotCached => (
+        "ページがキャッシュにありません: {} (--offlineを付けずに実行して下さい)",
+        "The page is not cached yet: {} (run it once without --offline)"
+    ),
+    ErrDownloadsFailed => (
+        "{} 件の問題のダウンロードに失敗しました。",
+        "Failed to download {} problems."
+    ),
+}
+
+/// Help text for the CLI, keyed by the subcommand path and argument id
+/// (`"judge"`, `"judge.summary_only"`, `"color"`).
+const HELP: &[(&str, &str, &str)] = &[
+    (
+        "",
+        "rlr はローカルでRecursionの問題を実行するためのツールです",
+        "rlr is a tool to run Recursion problems locally",
+    ),
+    (
+        "long_about",
+        "rlrはローカルでRecursionの問題を実行するためのツールです。\n\
+        rlrの簡単な使い方について説明します。\n\
+        詳細はGithubページ(https://github.com/kip2/rlr)を参照して下さい。\n\
+        \n\
+        1. loginオプションを使用して、Recursionへの初回ログインを行って下さい。\n\
+        2. downloadオプションで、取得したい問題ページのURLを引数に与え、テストケースの値を取得して下さい。\n\
+        3. 好きな言語・好きなエディタで問題を解くコードを書いてください。\n\
+        4. 3で書いたコードをシェルから実行するコマンドを用意し、コマンド実行文字列として与えて実行して下さい。 ",
+        "rlr is a tool to run Recursion problems locally.\n\
+        This is a short guide to using rlr.\n\
+        See the GitHub page (https://github.com/kip2/rlr) for details.\n\
+        \n\
+        1. Log in to Recursion for the first time with the login command.\n\
+        2. Get the test cases with the download command, passing the URL of the problem page.\n\
+        3. Write the code that solves the problem in any language and any editor.\n\
+        4. Prepare a shell command that runs the code from step 3, and pass it to the judge command as a string.",
+    ),
+    (
+        "usage",
+        "\n
+    login:    rlr login または rlr l
+    download: rlr download <URL> または rlr d <URL>
+    judge:    rlr judge <COMMAND> または rlr j <COMMAND>",
+        "\n
+    login:    rlr login or rlr l
+    download: rlr download <URL> or rlr d <URL>
+    judge:    rlr judge <COMMAND> or rlr j <COMMAND>",
+    ),
+    (
+        "color",
+        "出力の色付けを指定します。autoの場合、端末への出力時のみ色付けします(NO_COLOR環境変数にも従います)",
+        "When to color the output. auto colors only when writing to a terminal (NO_COLOR is respected)",
+    ),
+    (
+        "ascii",
+        "表の罫線などをASCII文字のみで出力します",
+        "Draw tables and other decorations with ASCII characters only",
+    ),
+    (
+        "quiet",
+        "警告とエラー以外のログを表示しません",
+        "Show only warnings and errors in the log",
+    ),
+    (
+        "verbose",
+        "詳細なログを表示します。-vで通信内容、-vvでデバッグ情報を表示します",
+        "Show more logs. -v shows network requests, -vv shows debug information",
+    ),
+    (
+        "log_file",
+        "デバッグレベルまでのログを指定したファイルに追記します",
+        "Append logs down to debug level to the given file",
+    ),
+    (
+        "proxy",
+        "通信に使用するプロキシのURL(http://、https://、socks5://)。network.proxyやHTTPS_PROXYなどの環境変数より優先します",
+        "Proxy URL for every request (http://, https://, socks5://), over network.proxy and variables such as HTTPS_PROXY",
+    ),
+    (
+        "ca_cert",
+        "追加で信頼するルート証明書(PEM)。複数指定できます",
+        "Extra root certificate (PEM) to trust. Can be given more than once",
+    ),
+    (
+        "pin_cert",
+        "指定した証明書(PEM)だけを信頼します。テスト用サーバーへの接続に使用します。--ca-certとは併用できません",
+        "Trust only the given certificate (PEM), e.g. that of a local test server. Cannot be used with --ca-cert",
+    ),
+    (
+        "insecure",
+        "証明書を検証しません。テスト用サーバーへの接続にのみ使用して下さい",
+        "Do not verify certificates. Use it only for a local test server",
+    ),
+    (
+        "lang",
+        "表示言語を指定します(RLR_LANG、LANG環境変数でも指定できます)",
+        "Display language (can also be set with the RLR_LANG or LANG environment variables)",
+    ),
+    (
+        "login",
+        "Recursionへのログイン処理を行います。",
+        "Log in to Recursion.",
+    ),
+    (
+        "download",
+        "指定したurlのテストケースをダウンロードします。",
+        "Download the test cases of the given URL.",
+    ),
+    (
+        "download.url",
+        "問題ページのURL、問題番号、範囲(1..50)、またはそれらのカンマ区切り。複数指定できます",
+        "URL of the problem page, the problem number, a range (1..50) or a comma-separated list of them. Can be given more than once",
+    ),
+    (
+        "download.from_file",
+        "ダウンロードする問題を1行ずつ書いたファイル(#以降はコメント)",
+        "File listing the problems to download, one or more per line (# starts a comment)",
+    ),
+    (
+        "download.jobs",
+        "複数の問題を同時に取得する数(最大4)",
+        "Number of pages fetched at the same time when downloading several problems (at most 4)",
+    ),
+    (
+        "download.offline",
+        "キャッシュした問題ページだけを使い、サイトに接続しません",
+        "Use the cached problem pages only, without going online",
+    ),
+    (
+        "download.refresh",
+        "キャッシュを使わず、問題ページを取得し直します",
+        "Fetch the problem pages again instead of using the cache",
+    ),
+    (
+        "download.template",
+        "使用するテンプレート名(templatesディレクトリ内の名前)、またはディレクトリのパス",
+        "Template to copy: a name in the template library, or a directory path",
+    ),
+    (
+        "download.stub",
+        "指定した言語で、問題の関数を呼び出すソースファイルのひな形を生成します",
+        "Generate a source file in the given language that calls the problem's function",
+    ),
+    (
+        "download.layout",
+        "テストケースの入力(.inファイル)の書き方。line: 1行に空白区切り、lines: 1行に1引数、counted: 配列の長さを前置、json: 1行に1つのJSON",
+        "How the inputs (.in files) are written. line: one line separated by spaces, lines: one argument per line, counted: arrays prefixed by their length, json: one JSON value per line",
+    ),
+    (
+        "list",
+        "ダッシュボードの問題一覧を表示します。",
+        "Show the problem index of the dashboard.",
+    ),
+    (
+        "list.offline",
+        "サイトにアクセスせず、前回取得した一覧を表示します",
+        "Show the index fetched last time without going online",
+    ),
+    (
+        "list.category",
+        "カテゴリーで絞り込みます(部分一致)",
+        "Only the problems whose category contains the text",
+    ),
+    (
+        "list.difficulty",
+        "難易度で絞り込みます(部分一致)",
+        "Only the problems whose difficulty contains the text",
+    ),
+    (
+        "list.solved",
+        "解答済みの問題だけを表示します",
+        "Only the solved problems",
+    ),
+    (
+        "list.unsolved",
+        "未解答の問題だけを表示します",
+        "Only the unsolved problems",
+    ),
+    (
+        "list.search",
+        "タイトルで絞り込みます(部分一致)",
+        "Only the problems whose title contains the text",
+    ),
+    ("list.sort", "並べ替えに使用する項目", "Column to sort by"),
+    ("list.reverse", "逆順に並べます", "Sort in reverse order"),
+    (
+        "submit",
+        "解答をRecursionに提出し、判定結果を表示します。",
+        "Submit the solution to Recursion and show the verdict.",
+    ),
+    (
+        "submit.file",
+        "提出するソースファイル(省略するとjudgeと同じように探します)",
+        "Source file to submit (found like judge does when omitted)",
+    ),
+    (
+        "submit.id",
+        "問題番号(省略するとproblem.tomlから読み取ります)",
+        "Problem number (read from problem.toml when omitted)",
+    ),
+    (
+        "submit.language",
+        "サイトに送る言語名(省略すると言語プロファイル名)",
+        "Language name sent to the site (the profile name when omitted)",
+    ),
+    (
+        "submit.whole_file",
+        "extractで取り出したコードではなく、ファイル全体を提出します",
+        "Submit the whole file instead of the code extract picks",
+    ),
+    (
+        "submit.no_wait",
+        "判定結果を待たずに終了します",
+        "Exit without waiting for the verdict",
+    ),
+    (
+        "import",
+        "保存した問題ページのHTMLファイルから、オフラインでテストケースを取り込みます。",
+        "Import the test cases from a saved problem page, offline.",
+    ),
+    (
+        "import.file",
+        "保存した問題ページのHTMLファイル",
+        "HTML file of the saved problem page",
+    ),
+    (
+        "import.id",
+        "問題番号(省略するとページ内のURLから読み取ります)",
+        "Problem number (read from the URL in the page when omitted)",
+    ),
+    (
+        "import.template",
+        "使用するテンプレート名(templatesディレクトリ内の名前)、またはディレクトリのパス",
+        "Template to copy: a name in the template library, or a directory path",
+    ),
+    (
+        "import.stub",
+        "指定した言語で、問題の関数を呼び出すソースファイルのひな形を生成します",
+        "Generate a source file in the given language that calls the problem's function",
+    ),
+    (
+        "import.layout",
+        "テストケースの入力(.inファイル)の書き方。line: 1行に空白区切り、lines: 1行に1引数、counted: 配列の長さを前置、json: 1行に1つのJSON",
+        "How the inputs (.in files) are written. line: one line separated by spaces, lines: one argument per line, counted: arrays prefixed by their length, json: one JSON value per line",
+    ),
+    (
+        "judge",
+        "カレントディレクトリにあるtestcaseディレクトリに対して、指定されたコマンドを使用してテストを実行します",
+        "Run the given command against the testcase directory in the current directory",
+    ),
+    (
+        "judge.judge_command",
+        "解答を実行するシェルコマンド(省略時はrlr.tomlの設定、run.sh、ソースファイルから自動で決定します)",
+        "Shell command that runs your solution (taken from rlr.toml, run.sh or the source files when omitted)",
+    ),
+    (
+        "judge.profile",
+        "コマンドを省略した場合に使用する言語プロファイル(python、rust、cppなど)",
+        "Language profile to use when no command is given (python, rust, cpp, ...)",
+    ),
+    (
+        "judge.function",
+        "標準入出力の代わりに、ソースファイルの関数を直接呼び出してジャッジします(python、javascript、ruby、php)。関数名を省略した場合はproblem.tomlの関数名を使います",
+        "Judge by calling the function in your source file instead of using stdin/stdout (python, javascript, ruby, php). The name defaults to the one in problem.toml",
+    ),
+    (
+        "judge.summary_only",
+        "ケース毎の詳細を表示せず、最後のサマリー表のみを表示します",
+        "Hide the per-case details and show only the summary table",
+    ),
+    (
+        "run",
+        "比較を行わずにコマンドを1回だけ実行し、標準出力・標準エラー出力・終了ステータス・実行時間・メモリ使用量を表示します",
+        "Run the command once and show its stdout, stderr, exit status, time and memory without judging",
+    ),
+    (
+        "run.run_command",
+        "解答を実行するシェルコマンド(省略時はrlr.tomlの設定、run.sh、ソースファイルから自動で決定します)",
+        "Shell command that runs your solution (taken from rlr.toml, run.sh or the source files when omitted)",
+    ),
+    (
+        "run.profile",
+        "コマンドを省略した場合に使用する言語プロファイル(python、rust、cppなど)",
+        "Language profile to use when no command is given (python, rust, cpp, ...)",
+    ),
+    (
+        "run.case",
+        "testcaseディレクトリのN番目のケースを入力にします",
+        "Use the N-th case in the testcase directory as input",
+    ),
+    (
+        "run.input",
+        "指定したファイルを入力にします(省略時は標準入力を使用します)",
+        "Use the given file as input (stdin is used when omitted)",
+    ),
+    (
+        "run.expected",
+        "指定したファイルと出力を比較し、差分を表示します",
+        "Compare the output with the given file and show the diff",
+    ),
+    (
+        "run.diff",
+        "--caseで指定したケースの.outファイルと出力を比較します",
+        "Compare the output with the .out file of the --case case",
+    ),
+    (
+        "extract",
+        "解答のソースファイルから、サイトのエディタに貼り付ける関数のコードだけを出力します",
+        "Print only the function code of your solution, ready to paste into the site's editor",
+    ),
+    (
+        "extract.file",
+        "解答のソースファイル",
+        "Source file of your solution",
+    ),
+    (
+        "extract.function",
+        "関数名(省略時はproblem.tomlの関数名を使います)",
+        "Function name (defaults to the one in problem.toml)",
+    ),
+    (
+        "config",
+        "rlr.tomlの設定を表示・変更します",
+        "Show or change the settings in rlr.toml",
+    ),
+    (
+        "config.get",
+        "設定値を表示します(例: judge.timeout)",
+        "Print a setting (e.g. judge.timeout)",
+    ),
+    (
+        "config.set",
+        "カレントディレクトリのrlr.tomlに設定値を書き込みます",
+        "Write a setting to rlr.toml in the current directory",
+    ),
+    (
+        "config.set.global",
+        "ユーザー設定ディレクトリのrlr.tomlに書き込みます",
+        "Write to rlr.toml in the user config directory instead",
+    ),
+    (
+        "config.list",
+        "読み込んだ設定ファイルと、反映後の全ての設定値を表示します",
+        "Print the loaded config files and every effective setting",
+    ),
+    (
+        "template",
+        "download時にコピーするテンプレートを管理します",
+        "Manage the templates copied on download",
+    ),
+    (
+        "template.list",
+        "ワークスペースとユーザー設定ディレクトリのテンプレートを一覧表示します",
+        "List the templates in the workspace and the user config directory",
+    ),
+    (
+        "template.add",
+        "ディレクトリをテンプレートとしてカレントディレクトリのtemplatesに追加します",
+        "Add a directory as a template to templates in the current directory",
+    ),
+    (
+        "template.add.name",
+        "テンプレート名(例: rust)",
+        "Template name (e.g. rust)",
+    ),
+    (
+        "template.add.dir",
+        "テンプレートにするディレクトリ",
+        "Directory to copy into the template library",
+    ),
+    (
+        "template.add.global",
+        "ユーザー設定ディレクトリのtemplatesに追加します",
+        "Add to templates in the user config directory instead",
+    ),
+    (
+        "template.remove",
+        "テンプレートを削除します",
+        "Remove a template",
+    ),
+    ("template.remove.name", "テンプレート名", "Template name"),
+    (
+        "template.remove.global",
+        "ユーザー設定ディレクトリのテンプレートを削除します",
+        "Remove the template in the user config directory instead",
+    ),
+    (
+        "cookie-path",
+        "Cookieファイルの保存パスを取得します。",
+        "Print the path of the cookie file.",
+    ),
+];
+
+fn help(key: &str) -> Option<&'static str> {
+    HELP.iter()
+        .find(|(k, _, _)| *k == key)
+        .map(|(_, ja, en)| match lang() {
+            Lang::Ja => *ja,
+            Lang::En => *en,
+        })
+}
+
+/// Applies the help text of the current language to the command tree.
+pub fn localize_command(cmd: Command) -> Command {
+    let mut cmd = cmd;
+    if let Some(about) = help("") {
+        cmd = cmd.about(about);
+    }
+    if let Some(long_about) = help("long_about") {
+        cmd = cmd.long_about(long_about);
+    }
+    if let Some(usage) = help("usage") {
+        cmd = cmd.override_usage(usage);
+    }
+    localize_args(cmd, "")
+}
+
+fn localize_args(cmd: Command, path: &str) -> Command {
+    let arg_ids = cmd
+        .get_arguments()
+        .map(|a| a.get_id().to_string())
+        .collect::<Vec<_>>();
+
+    let mut cmd = cmd;
+    for id in arg_ids {
+        let key = if path.is_empty() {
+            id.clone()
+        } else {
+            format!("{}.{}", path, id)
+        };
+        if let Some(text) = help(&key) {
+            cmd = cmd.mut_arg(id, |a| a.help(text));
+        }
+    }
+
+    let sub_names = cmd
+        .get_subcommands()
+        .map(|s| s.get_name().to_string())
+        .collect::<Vec<_>>();
+
+    for name in sub_names {
+        let sub_path = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", path, name)
+        };
+        cmd = cmd.mut_subcommand(name, |sub| {
+            let sub = match help(&sub_path) {
+                Some(about) => sub.about(about),
+                None => sub,
+            };
+            localize_args(sub, &sub_path)
+        });
+    }
+
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lang() {
+        assert_eq!(parse_lang("ja_JP.UTF-8"), Some(Lang::Ja));
+        assert_eq!(parse_lang("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(parse_lang("C"), None);
+    }
+
+    #[test]
+    fn test_lang_from_args() {
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            lang_from_args(args(&["rlr", "--lang", "en", "login"])),
+            Some(Lang::En)
+        );
+        assert_eq!(
+            lang_from_args(args(&["rlr", "judge", "--lang=ja", "cmd"])),
+            Some(Lang::Ja)
+        );
+        assert_eq!(lang_from_args(args(&["rlr", "judge", "cmd"])), None);
+    }
+
+    #[test]
+    fn test_format_msg() {
+        assert_eq!(format_msg("{} cases found", &[&3]), "3 cases found");
+        assert_eq!(
+            format_msg("slowest: {} sec (for {})", &[&"0.1", &"testcase-1"]),
+            "slowest: 0.1 sec (for testcase-1)"
+        );
+        assert_eq!(format_msg("no args {}", &[]), "no args {}");
+    }
+
+    #[test]
+    fn test_catalog_has_both_languages() {
+        assert_eq!(Msg::JudgeStart.text_in(Lang::En), "judge start");
+        assert_eq!(Msg::JudgeStart.text_in(Lang::Ja), "ジャッジを開始します");
+    }
+}
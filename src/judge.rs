@@ -1,31 +1,112 @@
 use crate::error::Error;
 use crate::file::{get_file_name, read_file};
+use crate::i18n::{Msg, tr};
+use crate::layout::InputConversion;
+use crate::literal::{Preset, Value, parse_value_with};
 use crate::messages::*;
 use colored::Colorize;
+use log::debug;
+use serde::{Deserialize, Serialize};
 use std::process::ExitStatus;
 use std::{
     fs::{self},
-    io::Write,
+    io::{self, Read, Write},
     process::{Child, Command, Stdio},
+    thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
-use wait_timeout::ChildExt;
 
+const SEPARATOR: &str = "---------------------------";
+const REASON_MAX_LEN: usize = 40;
+
+/// The outcome of one case, judged locally or by the site.
 #[derive(Debug)]
-struct JudgeResult {
-    case_name: String,
-    is_success: bool,
-    elapsed_time: Duration,
+pub struct JudgeResult {
+    pub case_name: String,
+    pub verdict: Verdict,
+    pub elapsed_time: Duration,
+    pub memory_kb: Option<u64>,
+    pub reason: String,
 }
 
-#[derive(Debug)]
-enum Verdict {
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Verdict {
     AC,
     WA,
     RE,
     TLE,
 }
 
+impl Verdict {
+    fn short_label(&self) -> String {
+        match self {
+            Verdict::AC => "AC".green().to_string(),
+            Verdict::WA => "WA".red().to_string(),
+            Verdict::RE => "RE".yellow().to_string(),
+            Verdict::TLE => "TLE".yellow().to_string(),
+        }
+    }
+
+    /// Reads a verdict as the site reports it, `AC` or `Accepted`.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_ascii_lowercase().as_str() {
+            "ac" | "accepted" => Some(Verdict::AC),
+            "wa" | "wrong answer" | "wrong_answer" => Some(Verdict::WA),
+            "re" | "runtime error" | "runtime_error" => Some(Verdict::RE),
+            "tle" | "time limit exceeded" | "time_limit_exceeded" => Some(Verdict::TLE),
+            _ => None,
+        }
+    }
+}
+
+/// How the program output is compared with the expected output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Comparator {
+    /// Equal after trimming surrounding whitespace.
+    #[default]
+    Exact,
+    /// Equal as whitespace separated tokens, ignoring line breaks and spacing.
+    Tokens,
+    /// Equal as values: both outputs are read as literals, the actual one the
+    /// way the language of `judge.preset` prints them (`True`, `['a', 'b']`).
+    Value,
+}
+
+impl Comparator {
+    fn matches(&self, actual: &str, expected: &str, preset: Preset) -> bool {
+        match self {
+            Comparator::Exact => actual.trim() == expected.trim(),
+            Comparator::Tokens => actual.split_whitespace().eq(expected.split_whitespace()),
+            Comparator::Value => match read_output(expected, Preset::Any) {
+                // a string printed without quotes may read as another literal
+                Value::Str(s) if s == actual.trim() => true,
+                expected => value_matches(&expected, &read_output(actual, preset), preset),
+            },
+        }
+    }
+}
+
+/// An output that is not a literal, e.g. `hello` without quotes, is a string.
+fn read_output(text: &str, preset: Preset) -> Value {
+    parse_value_with(text, preset).unwrap_or_else(|_| Value::Str(text.trim().to_string()))
+}
+
+fn value_matches(expected: &Value, actual: &Value, preset: Preset) -> bool {
+    match (expected, actual) {
+        (Value::Array(e), Value::Array(a)) => {
+            e.len() == a.len() && e.iter().zip(a).all(|(e, a)| value_matches(e, a, preset))
+        }
+        (Value::Int(i), Value::Float(x)) | (Value::Float(x), Value::Int(i)) => *i as f64 == *x,
+        (Value::Bool(b), Value::Int(i)) if preset.numeric_bools() => *i == *b as i64,
+        (Value::Str(s), Value::Int(_) | Value::Float(_) | Value::Bool(_)) => {
+            *s == actual.to_string()
+        }
+        _ => expected == actual,
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct TestFile {
     input_file: String,
@@ -42,60 +123,124 @@ impl TestFile {
 }
 
 impl JudgeResult {
-    fn new(case_name: String, is_success: bool, elapsed_time: Duration) -> Self {
+    pub fn new(
+        case_name: String,
+        verdict: Verdict,
+        elapsed_time: Duration,
+        memory_kb: Option<u64>,
+        reason: String,
+    ) -> Self {
         Self {
-            is_success,
-            elapsed_time,
             case_name,
+            verdict,
+            elapsed_time,
+            memory_kb,
+            reason,
         }
     }
+
+    fn is_success(&self) -> bool {
+        self.verdict == Verdict::AC
+    }
+}
+
+pub struct JudgeOptions {
+    pub summary_only: bool,
+    pub build: Option<String>,
+    pub timeout: Duration,
+    pub comparator: Comparator,
+    pub preset: Preset,
+    pub testcase_dir: String,
+    /// Set for `judge --function`, the input given to the harness.
+    pub input_conversion: Option<InputConversion>,
+}
+
+/// Result of running the judged command once against a single input.
+#[derive(Debug)]
+pub struct Execution {
+    /// `None` when the process was killed because it exceeded the time limit.
+    pub status: Option<ExitStatus>,
+    pub stdout: String,
+    pub stderr: String,
+    pub elapsed: Duration,
+    /// Peak resident set size in KiB, when the platform can report it.
+    pub memory_kb: Option<u64>,
 }
 
-pub fn judge(command_str: &str) -> Result<(), Error> {
-    let dir_path = "./testcase";
-    let file_list = create_testfile_list(dir_path)?;
+pub fn judge(command_str: &str, options: &JudgeOptions) -> Result<(), Error> {
+    if let Some(build_command) = &options.build {
+        build(build_command)?;
+    }
+
+    let file_list = create_testfile_list(&options.testcase_dir)?;
     let version_info = format!("Recursion local runner {}", env!("CARGO_PKG_VERSION"));
-    let mut slowest_elapsed_time = Duration::new(0, 0);
-    let mut slowest_elapsed_case = String::new();
 
     // start message
     println!("[{}] {}", *INFO_LABEL, version_info);
-    println!("[{}] {} cases found", *INFO_LABEL, file_list.len());
-    println!("[{}] judge start", *INFO_LABEL);
+    println!(
+        "[{}] {}",
+        *INFO_LABEL,
+        tr!(Msg::CasesFound, file_list.len())
+    );
+    println!("[{}] {}", *INFO_LABEL, Msg::JudgeStart);
 
     println!();
-    println!("---------------------------");
-    println!();
+    if !options.summary_only {
+        println!("{}", SEPARATOR);
+        println!();
+    }
 
     // judge
-    let total_case = file_list.len();
-    let mut success_case = 0;
+    let mut results = Vec::<JudgeResult>::new();
 
     for testfile in file_list {
         let input_file_path = testfile.input_file;
         let output_file_path = testfile.output_file;
-        let result = judge_test_case(&input_file_path, &output_file_path, command_str)?;
+        let result = judge_test_case(&input_file_path, &output_file_path, command_str, options)?;
+
+        results.push(result);
+    }
+
+    // end message
+    println!("[{}] {}", *INFO_LABEL, Msg::JudgeEnd);
+    println!();
+
+    print_results(&results);
 
+    Ok(())
+}
+
+/// Prints the summary table, the slowest case and the number of passed cases.
+pub fn print_results(results: &[JudgeResult]) {
+    let total_case = results.len();
+    let mut success_case = 0;
+    let mut slowest_elapsed_time = Duration::new(0, 0);
+    let mut slowest_elapsed_case = String::new();
+
+    for result in results {
         // increment success case count
-        if result.is_success {
+        if result.is_success() {
             success_case += 1;
         }
 
         // compare elapsed time
         if slowest_elapsed_time < result.elapsed_time {
             slowest_elapsed_time = result.elapsed_time;
-            slowest_elapsed_case = result.case_name;
+            slowest_elapsed_case = result.case_name.clone();
         }
     }
 
-    // end message
-    println!("[{}] end judge", *INFO_LABEL);
+    print_summary_table(results);
+    println!();
 
     println!(
-        "[{}] slowest: {:.6} sec (for {})",
+        "[{}] {}",
         *INFO_LABEL,
-        slowest_elapsed_time.as_secs_f64(),
-        slowest_elapsed_case
+        tr!(
+            Msg::Slowest,
+            format!("{:.6}", slowest_elapsed_time.as_secs_f64()),
+            slowest_elapsed_case
+        )
     );
 
     if success_case == total_case {
@@ -118,59 +263,85 @@ pub fn judge(command_str: &str) -> Result<(), Error> {
             *FAILURE_LABEL, *FAILED_LABEL, total_case
         );
     }
-
-    Ok(())
 }
 
 fn judge_test_case(
     input_path: &str,
     output_path: &str,
     command_str: &str,
+    options: &JudgeOptions,
 ) -> Result<JudgeResult, Error> {
-    let timeout = Duration::from_secs(3);
-
-    // start time measurement
-    let start = Instant::now();
+    let timeout = options.timeout;
 
     let settion_title = get_file_name(input_path)?;
 
-    println!("[{}] {}", *INFO_LABEL, settion_title);
-
     let input_contents = read_file(input_path)?;
     let output_contents = read_file(output_path)?;
 
-    let mut child = Command::new("sh")
-        .arg("-c")
-        .arg(command_str)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()?;
+    let stdin = match &options.input_conversion {
+        Some(conversion) => conversion.apply(&input_contents)?,
+        None => input_contents.clone(),
+    };
+    let execution = execute(command_str, &stdin, timeout)?;
 
-    write_to_stdin(&mut child, &input_contents)?;
+    let mut actual = String::new();
 
-    let wait_result = child.wait_timeout(timeout)?;
+    let verdict = determine_verdict(
+        &execution,
+        &output_contents,
+        options.comparator,
+        options.preset,
+        &mut actual,
+    );
+    let reason = verdict_reason(verdict, &execution, &actual, &output_contents, timeout);
 
-    let mut actual = String::new();
+    if !options.summary_only {
+        print_case_detail(
+            settion_title,
+            verdict,
+            &execution,
+            &input_contents,
+            &actual,
+            &output_contents,
+            timeout,
+        );
+    }
 
-    let verdict = determine_verdict(child, wait_result, &output_contents, &mut actual)?;
+    Ok(JudgeResult::new(
+        settion_title.to_string(),
+        verdict,
+        execution.elapsed,
+        execution.memory_kb,
+        reason,
+    ))
+}
 
-    let duration = start.elapsed();
+fn print_case_detail(
+    title: &str,
+    verdict: Verdict,
+    execution: &Execution,
+    input_contents: &str,
+    actual: &str,
+    output_contents: &str,
+    timeout: Duration,
+) {
+    let duration = execution.elapsed;
+    let time = tr!(Msg::CaseTime, format!("{:.6}", duration.as_secs_f64()));
 
-    let mut is_success = false;
+    println!("[{}] {}", *INFO_LABEL, title);
 
     match verdict {
         Verdict::AC => {
-            println!("[{}] time: {:.6} sec", *INFO_LABEL, duration.as_secs_f64());
+            println!("[{}] {}", *INFO_LABEL, time);
             println!("[{}] {}", *SUCCESS_LABEL, *AC_LABEL);
-            is_success = true;
         }
         Verdict::WA => {
-            println!("[{}] time: {:.6} sec", *INFO_LABEL, duration.as_secs_f64());
+            println!("[{}] {}", *INFO_LABEL, time);
             println!("[{}] {}", *FAILURE_LABEL, *WA_LABEL);
-            println!("input:\n{}", input_contents);
-            println!("output:\n{}", actual);
+            println!("{}\n{}", Msg::InputHeading, input_contents);
+            println!("{}\n{}", Msg::OutputHeading, actual);
             println!();
-            println!("expected:\n{}", output_contents);
+            println!("{}\n{}", Msg::ExpectedHeading, output_contents);
         }
         Verdict::RE => {
             println!("[{}] {}", *FAILURE_LABEL, *RE_LABEL);
@@ -180,65 +351,486 @@ fn judge_test_case(
             println!(
                 "[{}] {}",
                 *FAILURE_LABEL,
-                "The program ran for more than 3 seconds.".red()
+                tr!(Msg::TimeLimitExceeded, format_secs(timeout)).red()
             );
         }
     }
 
+    if !execution.stderr.is_empty() {
+        println!(
+            "{}\n{}",
+            Msg::StderrHeading,
+            trim_one_newline(&execution.stderr)
+        );
+    }
+
     println!();
-    println!("---------------------------");
+    println!("{}", SEPARATOR);
     println!();
+}
 
-    Ok(JudgeResult::new(
-        settion_title.to_string(),
-        is_success,
-        duration,
-    ))
+pub enum RunInput {
+    Case(usize),
+    File(String),
+    Stdin,
+}
+
+pub struct RunOptions {
+    pub input: RunInput,
+    /// File to diff stdout against. With `RunInput::Case`, `--diff` resolves
+    /// this to the case's `.out` file.
+    pub expected: Option<String>,
+    pub build: Option<String>,
+    pub timeout: Duration,
+    pub comparator: Comparator,
+    pub preset: Preset,
+    pub testcase_dir: String,
+}
+
+/// Runs the command once and shows everything it produced, without judging
+/// it unless an expected output is given.
+pub fn run_once(command_str: &str, options: &RunOptions) -> Result<(), Error> {
+    if let Some(build_command) = &options.build {
+        build(build_command)?;
+    }
+
+    let input = match &options.input {
+        RunInput::Case(n) => {
+            let path = case_file_path(&options.testcase_dir, *n, "in");
+            println!("[{}] {}", *INFO_LABEL, tr!(Msg::RunInputFrom, path));
+            read_file(&path)?
+        }
+        RunInput::File(path) => {
+            println!("[{}] {}", *INFO_LABEL, tr!(Msg::RunInputFrom, path));
+            read_file(path)?
+        }
+        RunInput::Stdin => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            input
+        }
+    };
+
+    let execution = execute(command_str, &input, options.timeout)?;
+
+    println!("{}", Msg::StdoutHeading);
+    print!("{}", execution.stdout);
+    if !execution.stdout.is_empty() && !execution.stdout.ends_with('\n') {
+        println!();
+    }
+
+    if !execution.stderr.is_empty() {
+        println!("{}", Msg::StderrHeading);
+        print!("{}", execution.stderr);
+        if !execution.stderr.ends_with('\n') {
+            println!();
+        }
+    }
+    println!();
+
+    match execution.status {
+        Some(status) => println!(
+            "[{}] {}",
+            *INFO_LABEL,
+            tr!(Msg::RunExitStatus, describe_exit_status(status))
+        ),
+        None => println!(
+            "[{}] {}",
+            *FAILURE_LABEL,
+            tr!(Msg::TimeLimitExceeded, format_secs(options.timeout)).red()
+        ),
+    }
+    println!(
+        "[{}] {}",
+        *INFO_LABEL,
+        tr!(
+            Msg::CaseTime,
+            format!("{:.6}", execution.elapsed.as_secs_f64())
+        )
+    );
+    if let Some(kb) = execution.memory_kb {
+        println!("[{}] {}", *INFO_LABEL, tr!(Msg::RunMemory, kb));
+    }
+
+    if let Some(expected_path) = &options.expected {
+        let expected = read_file(expected_path)?;
+        let mut actual = String::new();
+        let verdict = determine_verdict(
+            &execution,
+            &expected,
+            options.comparator,
+            options.preset,
+            &mut actual,
+        );
+
+        println!();
+        match verdict {
+            Verdict::AC => println!("[{}] {}", *SUCCESS_LABEL, *AC_LABEL),
+            Verdict::WA => {
+                println!("[{}] {}", *FAILURE_LABEL, *WA_LABEL);
+                print_diff(&expected, &actual);
+            }
+            Verdict::RE => println!("[{}] {}", *FAILURE_LABEL, *RE_LABEL),
+            Verdict::TLE => println!("[{}] {}", *FAILURE_LABEL, *TLE_LABEL),
+        }
+    }
+
+    Ok(())
+}
+
+pub fn case_file_path(testcase_dir: &str, case: usize, extension: &str) -> String {
+    format!("./{}/testcase-{}.{}", testcase_dir, case, extension)
+}
+
+/// Runs the build command once, showing its output as it goes.
+fn build(command_str: &str) -> Result<(), Error> {
+    println!("[{}] {}", *INFO_LABEL, tr!(Msg::Build, command_str));
+    debug!("spawn build `sh -c {:?}`", command_str);
+
+    let status = Command::new("sh").arg("-c").arg(command_str).status()?;
+    if !status.success() {
+        return Err(Error::BuildFailed(format!(
+            "{} ({})",
+            command_str,
+            describe_exit_status(status)
+        )));
+    }
+    println!();
+    Ok(())
+}
+
+fn format_secs(duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    if secs.fract() == 0.0 {
+        format!("{}", secs)
+    } else {
+        format!("{:.1}", secs)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Expected(&'a str),
+    Actual(&'a str),
+}
+
+/// Compares line by line; good enough for the short outputs of these problems.
+fn diff_lines<'a>(expected: &'a str, actual: &'a str) -> Vec<DiffLine<'a>> {
+    let expected = expected.trim_end().lines().collect::<Vec<_>>();
+    let actual = actual.trim_end().lines().collect::<Vec<_>>();
+
+    let mut diff = Vec::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e.trim_end() == a.trim_end() => diff.push(DiffLine::Same(e)),
+            (e, a) => {
+                if let Some(e) = e {
+                    diff.push(DiffLine::Expected(e));
+                }
+                if let Some(a) = a {
+                    diff.push(DiffLine::Actual(a));
+                }
+            }
+        }
+    }
+    diff
+}
+
+fn print_diff(expected: &str, actual: &str) {
+    println!("{}", Msg::DiffLegend);
+    for line in diff_lines(expected, actual) {
+        match line {
+            DiffLine::Same(l) => println!("  {}", l),
+            DiffLine::Expected(l) => println!("{}", format!("- {}", l).red()),
+            DiffLine::Actual(l) => println!("{}", format!("+ {}", l).green()),
+        }
+    }
+}
+
+fn print_summary_table(results: &[JudgeResult]) {
+    let header = [
+        "#".to_string(),
+        tr!(Msg::ColumnCase),
+        tr!(Msg::ColumnVerdict),
+        tr!(Msg::ColumnTime),
+        tr!(Msg::ColumnMemory),
+        tr!(Msg::ColumnReason),
+    ];
+    let header = header.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let rows = results
+        .iter()
+        .enumerate()
+        .map(|(i, result)| {
+            vec![
+                (i + 1).to_string(),
+                result.case_name.clone(),
+                format!("{:?}", result.verdict),
+                format!("{:.6}", result.elapsed_time.as_secs_f64()),
+                result
+                    .memory_kb
+                    .map(|kb| kb.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                result.reason.clone(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    // pad first, then colorize, so escape codes do not break the alignment
+    print_table(&header, &rows, |row, column, cell| {
+        if column == 2 {
+            results[row].verdict.short_label()
+        } else {
+            cell.to_string()
+        }
+    });
+}
+
+fn verdict_reason(
+    verdict: Verdict,
+    execution: &Execution,
+    actual: &str,
+    expected: &str,
+    timeout: Duration,
+) -> String {
+    match verdict {
+        Verdict::AC => String::new(),
+        Verdict::WA => tr!(
+            Msg::ReasonWrongAnswer,
+            shorten(expected.trim()),
+            shorten(actual.trim())
+        ),
+        Verdict::RE => {
+            let status = execution
+                .status
+                .map(describe_exit_status)
+                .unwrap_or_default();
+            match execution
+                .stderr
+                .lines()
+                .rev()
+                .find(|l| !l.trim().is_empty())
+            {
+                Some(line) => shorten(&format!("{}: {}", status, line.trim())),
+                None => status,
+            }
+        }
+        Verdict::TLE => tr!(Msg::ReasonTimeLimit, format_secs(timeout)),
+    }
+}
+
+fn describe_exit_status(status: ExitStatus) -> String {
+    if let Some(code) = status.code() {
+        return tr!(Msg::ExitCode, code);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return tr!(Msg::ExitSignal, signal);
+        }
+    }
+
+    tr!(Msg::ExitTerminated)
+}
+
+fn shorten(s: &str) -> String {
+    let one_line = s.replace('\n', "\\n");
+    if one_line.chars().count() <= REASON_MAX_LEN {
+        one_line
+    } else {
+        let head = one_line
+            .chars()
+            .take(REASON_MAX_LEN - 3)
+            .collect::<String>();
+        format!("{}...", head)
+    }
 }
 
 fn determine_verdict(
-    mut child: Child,
-    wait_result: Option<ExitStatus>,
+    execution: &Execution,
     expected_output: &str,
+    comparator: Comparator,
+    preset: Preset,
     actual_output: &mut String,
-) -> Result<Verdict, Error> {
-    let status = if let Some(status) = wait_result {
+) -> Verdict {
+    let status = if let Some(status) = execution.status {
         status
     } else {
-        let _ = child.kill();
-        return Ok(Verdict::TLE);
+        return Verdict::TLE;
     };
 
     if !status.success() {
-        return Ok(Verdict::RE);
+        return Verdict::RE;
     }
 
-    let output = child.wait_with_output()?;
+    *actual_output = trim_one_newline(&execution.stdout).to_string();
 
-    *actual_output = String::from_utf8_lossy(&output.stdout).to_string();
-    *actual_output = trim_one_newline(actual_output).to_string();
-
-    if actual_output.trim() == expected_output.trim() {
-        Ok(Verdict::AC)
+    if comparator.matches(actual_output, expected_output, preset) {
+        Verdict::AC
     } else {
-        Ok(Verdict::WA)
+        Verdict::WA
     }
 }
 
 fn trim_one_newline(s: &str) -> &str {
-    if s.ends_with('\n') {
-        &s[..s.len() - 1]
+    s.strip_suffix('\n').unwrap_or(s)
+}
+
+/// Runs `command_str` through the shell, feeding `input` to its stdin.
+///
+/// The child is killed once `timeout` elapses; stdout and stderr are read on
+/// background threads so a chatty program cannot block on a full pipe.
+pub fn execute(command_str: &str, input: &str, timeout: Duration) -> Result<Execution, Error> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command_str)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    debug!("spawned `sh -c {:?}` (pid {})", command_str, child.id());
+
+    // start time measurement
+    let start = Instant::now();
+
+    let stdout_reader = spawn_reader(child.stdout.take())?;
+    let stderr_reader = spawn_reader(child.stderr.take())?;
+
+    write_to_stdin(&mut child, input)?;
+
+    let (status, memory_kb) = wait_with_timeout(&mut child, timeout)?;
+    let elapsed = start.elapsed();
+
+    debug!(
+        "pid {} finished: {}, {:.6} sec, {} KB",
+        child.id(),
+        status.map_or("timed out".to_string(), |s| s.to_string()),
+        elapsed.as_secs_f64(),
+        memory_kb.map_or("-".to_string(), |kb| kb.to_string())
+    );
+
+    // A timed out program may have left grandchildren holding the pipes open,
+    // so its output is not waited for.
+    let (stdout, stderr) = if status.is_some() {
+        (join_reader(stdout_reader), join_reader(stderr_reader))
+    } else {
+        (String::new(), String::new())
+    };
+
+    Ok(Execution {
+        status,
+        stdout,
+        stderr,
+        elapsed,
+        memory_kb,
+    })
+}
+
+fn spawn_reader<R: Read + Send + 'static>(source: Option<R>) -> Result<JoinHandle<String>, Error> {
+    let mut source = source.ok_or(Error::Internal(
+        "Failed to capture output in spawn_reader".to_string(),
+    ))?;
+
+    Ok(thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = source.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).to_string()
+    }))
+}
+
+fn join_reader(handle: JoinHandle<String>) -> String {
+    handle.join().unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> Result<(Option<ExitStatus>, Option<u64>), Error> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    let start = Instant::now();
+    let mut timed_out = false;
+
+    loop {
+        let mut status = 0;
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        let options = if timed_out { 0 } else { libc::WNOHANG };
+
+        // wait4 is used instead of Child::wait so the peak memory of the
+        // finished process can be read from its rusage.
+        let ret = unsafe { libc::wait4(pid, &mut status, options, &mut usage) };
+
+        if ret == pid {
+            let memory_kb = max_rss_kb(&usage);
+            if timed_out {
+                return Ok((None, Some(memory_kb)));
+            }
+            return Ok((Some(ExitStatus::from_raw(status)), Some(memory_kb)));
+        }
+
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err.into());
+        }
+
+        if start.elapsed() >= timeout {
+            let _ = child.kill();
+            timed_out = true;
+            continue;
+        }
+
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[cfg(unix)]
+fn max_rss_kb(usage: &libc::rusage) -> u64 {
+    let max_rss = usage.ru_maxrss.max(0) as u64;
+    // macOS reports bytes, Linux and the BSDs report KiB
+    if cfg!(target_os = "macos") {
+        max_rss / 1024
     } else {
-        s
+        max_rss
+    }
+}
+
+#[cfg(not(unix))]
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> Result<(Option<ExitStatus>, Option<u64>), Error> {
+    use wait_timeout::ChildExt;
+
+    match child.wait_timeout(timeout)? {
+        Some(status) => Ok((Some(status), None)),
+        None => {
+            let _ = child.kill();
+            let _ = child.wait();
+            Ok((None, None))
+        }
     }
 }
 
 fn write_to_stdin(child: &mut Child, contents: &str) -> Result<(), Error> {
-    let stdin = child.stdin.as_mut().ok_or(Error::Internal(
+    // take stdin so that it is closed after writing and the program sees EOF
+    let mut stdin = child.stdin.take().ok_or(Error::Internal(
         "Failed to stdin in write_to_stdin".to_string(),
     ))?;
-    stdin.write_all(contents.as_bytes())?;
-    Ok(())
+
+    match stdin.write_all(contents.as_bytes()) {
+        // the program may exit without reading its input
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
 }
 
 fn create_testfile_list(path: &str) -> Result<Vec<TestFile>, Error> {
@@ -273,3 +865,161 @@ fn conv_string_to_testfiles(file_list: Vec<String>) -> Vec<TestFile> {
 
     testfiles_list
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config, harness::function_command, layout::InputLayout, parser::ValueType,
+    };
+
+    #[test]
+    fn test_shorten() {
+        assert_eq!(shorten("1 2 3"), "1 2 3");
+        assert_eq!(shorten("a\nb"), "a\\nb");
+
+        let long = "x".repeat(REASON_MAX_LEN + 10);
+        let actual = shorten(&long);
+        assert_eq!(actual.chars().count(), REASON_MAX_LEN);
+        assert!(actual.ends_with("..."));
+    }
+
+    #[test]
+    fn test_conv_string_to_testfiles() {
+        let file_list = vec![
+            "./testcase/testcase-1.in".to_string(),
+            "./testcase/testcase-1.out".to_string(),
+            "./testcase/testcase-2.in".to_string(),
+            "./testcase/testcase-2.out".to_string(),
+        ];
+
+        let expected = vec![
+            TestFile::new(
+                "./testcase/testcase-1.in".to_string(),
+                "./testcase/testcase-1.out".to_string(),
+            ),
+            TestFile::new(
+                "./testcase/testcase-2.in".to_string(),
+                "./testcase/testcase-2.out".to_string(),
+            ),
+        ];
+
+        assert_eq!(conv_string_to_testfiles(file_list), expected);
+    }
+
+    #[test]
+    fn test_comparator() {
+        let preset = Preset::Any;
+        assert!(Comparator::Exact.matches("1 2\n", "1 2", preset));
+        assert!(!Comparator::Exact.matches("1  2", "1 2", preset));
+        assert!(Comparator::Tokens.matches("1  2\n3", "1 2 3", preset));
+        assert!(!Comparator::Tokens.matches("1 2", "1 2 3", preset));
+    }
+
+    #[test]
+    fn test_value_comparator() {
+        let value = Comparator::Value;
+        assert!(value.matches("True", "true", Preset::Python));
+        assert!(value.matches("[1, 2, 3]\n", "[1,2,3]", Preset::Python));
+        assert!(value.matches("['a', 'b']", "[\"a\",\"b\"]", Preset::Python));
+        assert!(value.matches("hello", "\"hello\"", Preset::Any));
+        assert!(value.matches("\"hello\"", "hello", Preset::Any));
+        assert!(value.matches("2.0", "2", Preset::Any));
+        assert!(value.matches("[a, b]", "[\"a\",\"b\"]", Preset::Java));
+        assert!(value.matches("1", "true", Preset::Cpp));
+        assert!(!value.matches("1", "true", Preset::Python));
+        assert!(!value.matches("True", "true", Preset::Strict));
+        assert!(!value.matches("[1,2]", "[1,2,3]", Preset::Any));
+        assert!(value.matches("123", "\"123\"", Preset::Any));
+        assert!(value.matches("1.50", "\"1.50\"", Preset::Any));
+        assert!(value.matches("[12, true]", "[\"12\",\"true\"]", Preset::Java));
+        assert!(!value.matches("124", "\"123\"", Preset::Any));
+    }
+
+    #[test]
+    fn test_format_secs() {
+        assert_eq!(format_secs(Duration::from_secs(3)), "3");
+        assert_eq!(format_secs(Duration::from_millis(1500)), "1.5");
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let actual = diff_lines("1\n2\n3\n", "1\n5\n3\n4");
+
+        let expected = vec![
+            DiffLine::Same("1"),
+            DiffLine::Expected("2"),
+            DiffLine::Actual("5"),
+            DiffLine::Same("3"),
+            DiffLine::Actual("4"),
+        ];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute() {
+        let execution = execute("cat; echo err >&2", "1 2", Duration::from_secs(3)).unwrap();
+
+        assert!(execution.status.unwrap().success());
+        assert_eq!(execution.stdout, "1 2");
+        assert_eq!(execution.stderr, "err\n");
+        assert!(execution.memory_kb.is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_timeout() {
+        let execution = execute("sleep 5", "", Duration::from_millis(100)).unwrap();
+
+        assert!(execution.status.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_judge_function_returning_strings() {
+        let dir = std::env::temp_dir().join(format!("rlr-judge-function-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("main.py"),
+            "def shout(words, n):\n    return [w + \"!\" * n for w in words]\n",
+        )
+        .unwrap();
+        let words = ["a b", "c,d"].map(|s| Value::Str(s.to_string()));
+        let input = InputLayout::Line.format(&[Value::Array(words.to_vec()), Value::Int(2)]);
+        let output = Value::Array(
+            ["a b!!", "c,d!!"]
+                .map(|s| Value::Str(s.to_string()))
+                .to_vec(),
+        );
+        let input_path = dir.join("testcase-1.in").to_string_lossy().to_string();
+        let output_path = dir.join("testcase-1.out").to_string_lossy().to_string();
+        fs::write(&input_path, input).unwrap();
+        fs::write(&output_path, output.to_string()).unwrap();
+
+        let (command, _harness) = function_command(
+            Some("shout".to_string()),
+            Some("python".to_string()),
+            &Config::default(),
+        )
+        .unwrap();
+        let options = JudgeOptions {
+            summary_only: true,
+            build: None,
+            timeout: Duration::from_secs(10),
+            comparator: Comparator::Exact,
+            preset: Preset::Any,
+            testcase_dir: dir.to_string_lossy().to_string(),
+            input_conversion: Some(InputConversion {
+                from: InputLayout::Line,
+                arg_types: vec![ValueType::Array(Box::new(ValueType::Str)), ValueType::Int],
+            }),
+        };
+        let command = format!("cd '{}' && {}", dir.display(), command.run);
+        let result = judge_test_case(&input_path, &output_path, &command, &options).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result.verdict, Verdict::AC);
+    }
+}
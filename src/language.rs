@@ -0,0 +1,316 @@
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::{config::Config, error::Error};
+
+const RUN_SCRIPT: &str = "run.sh";
+
+/// How to build and run a solution written in one language.
+///
+/// `{source}` in `build` and `run` is replaced with the source file that was
+/// found in the problem directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// File name used to detect the language, matched case-insensitively.
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
+    pub run: String,
+}
+
+impl Profile {
+    fn new(source: &str, build: Option<&str>, run: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            build: build.map(|b| b.to_string()),
+            run: run.to_string(),
+        }
+    }
+}
+
+/// The command `judge` and `run` will use, and where it came from.
+#[derive(Debug, PartialEq)]
+pub struct ResolvedCommand {
+    pub run: String,
+    pub build: Option<String>,
+    pub origin: String,
+}
+
+pub fn builtin_profiles() -> Vec<(&'static str, Profile)> {
+    vec![
+        ("python", Profile::new("main.py", None, "python3 {source}")),
+        (
+            "rust",
+            Profile::new("main.rs", Some("rustc -O -o main {source}"), "./main"),
+        ),
+        (
+            "cargo",
+            Profile::new(
+                "Cargo.toml",
+                Some("cargo build --release --quiet"),
+                "cargo run --release --quiet",
+            ),
+        ),
+        (
+            "c",
+            Profile::new("main.c", Some("gcc -O2 -o main {source}"), "./main"),
+        ),
+        (
+            "cpp",
+            Profile::new(
+                "main.cpp",
+                Some("g++ -O2 -std=c++17 -o main {source}"),
+                "./main",
+            ),
+        ),
+        (
+            "java",
+            Profile::new("Main.java", Some("javac {source}"), "java Main"),
+        ),
+        (
+            "d",
+            Profile::new("main.d", Some("dmd -O -of=main {source}"), "./main"),
+        ),
+        (
+            "haskell",
+            Profile::new("Main.hs", Some("ghc -O2 -o main {source}"), "./main"),
+        ),
+        ("elixir", Profile::new("main.ex", None, "elixir {source}")),
+        (
+            "commonlisp",
+            Profile::new("main.lsp", None, "sbcl --script {source}"),
+        ),
+        ("javascript", Profile::new("main.js", None, "node {source}")),
+        (
+            "go",
+            Profile::new("main.go", Some("go build -o main {source}"), "./main"),
+        ),
+        ("ruby", Profile::new("main.rb", None, "ruby {source}")),
+        ("php", Profile::new("main.php", None, "php {source}")),
+    ]
+}
+
+/// User profiles from the config come first, so they can replace a built-in
+/// profile of the same name.
+fn all_profiles(config: &Config) -> Vec<(String, Profile)> {
+    let mut profiles = config
+        .profiles
+        .iter()
+        .map(|(name, profile)| (name.clone(), profile.clone()))
+        .collect::<Vec<_>>();
+
+    for (name, profile) in builtin_profiles() {
+        if !config.profiles.contains_key(name) {
+            profiles.push((name.to_string(), profile));
+        }
+    }
+    profiles
+}
+
+pub fn find_profile(name: &str, config: &Config) -> Result<Profile, Error> {
+    all_profiles(config)
+        .into_iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, profile)| profile)
+        .ok_or(Error::UnknownProfile(name.to_string()))
+}
+
+/// Finds the first profile whose source file exists in `dir`, returning the
+/// profile name, the profile and the actual file name.
+pub fn detect_profile(dir: &Path, config: &Config) -> Option<(String, Profile, String)> {
+    let files = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str().map(|s| s.to_string()))
+        .collect::<Vec<_>>();
+
+    all_profiles(config)
+        .into_iter()
+        .find_map(|(name, profile)| {
+            let found = files
+                .iter()
+                .find(|file| file.eq_ignore_ascii_case(&profile.source))?
+                .clone();
+            debug!("detected profile {} from {}", name, found);
+            Some((name, profile, found))
+        })
+}
+
+/// The profile whose source file has the same extension as `file`.
+pub fn profile_for_file(file: &str, config: &Config) -> Option<String> {
+    let extension = Path::new(file).extension()?.to_str()?.to_ascii_lowercase();
+    all_profiles(config)
+        .into_iter()
+        .find(|(_, profile)| {
+            Path::new(&profile.source)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case(&extension))
+        })
+        .map(|(name, _)| name)
+}
+
+/// Picks the command in this order: the command argument, `--profile`,
+/// `judge.command`, `judge.profile`, a `run.sh`, then language detection.
+pub fn resolve_command(
+    command: Option<String>,
+    profile: Option<String>,
+    config: &Config,
+) -> Result<ResolvedCommand, Error> {
+    if let Some(run) = command {
+        return Ok(ResolvedCommand {
+            run,
+            build: config.judge.build.clone(),
+            origin: "argument".to_string(),
+        });
+    }
+
+    if let Some(name) = profile {
+        let profile = find_profile(&name, config)?;
+        let source = profile.source.clone();
+        return Ok(from_profile(&name, profile, &source, config));
+    }
+
+    if let Some(run) = &config.judge.command {
+        return Ok(ResolvedCommand {
+            run: run.clone(),
+            build: config.judge.build.clone(),
+            origin: "judge.command".to_string(),
+        });
+    }
+
+    if let Some(name) = &config.judge.profile {
+        let profile = find_profile(name, config)?;
+        let source = profile.source.clone();
+        return Ok(from_profile(name, profile, &source, config));
+    }
+
+    let cwd = std::env::current_dir()?;
+    let run_script = cwd.join(RUN_SCRIPT);
+    if run_script.is_file() {
+        if is_launcher_script(&fs::read_to_string(&run_script)?) {
+            warn!(
+                "{} calls rlr itself, so it is not used as the judge command",
+                RUN_SCRIPT
+            );
+        } else {
+            return Ok(ResolvedCommand {
+                run: format!("sh {}", RUN_SCRIPT),
+                build: config.judge.build.clone(),
+                origin: RUN_SCRIPT.to_string(),
+            });
+        }
+    }
+
+    if let Some((name, profile, source)) = detect_profile(&cwd, config) {
+        return Ok(from_profile(&name, profile, &source, config));
+    }
+
+    Err(Error::NoJudgeCommand)
+}
+
+fn from_profile(name: &str, profile: Profile, source: &str, config: &Config) -> ResolvedCommand {
+    ResolvedCommand {
+        run: profile.run.replace("{source}", source),
+        build: config
+            .judge
+            .build
+            .clone()
+            .or(profile.build)
+            .map(|b| b.replace("{source}", source)),
+        origin: format!("profile {}", name),
+    }
+}
+
+/// The run.sh scripts in the usage documents build and then call `rlr judge`
+/// themselves; running those for every case would recurse.
+fn is_launcher_script(contents: &str) -> bool {
+    contents.lines().any(|line| {
+        let line = line.trim_start();
+        !line.starts_with('#') && (line.starts_with("rlr ") || line.contains(" rlr "))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_builtin_profile() {
+        let config = Config::default();
+        let profile = find_profile("python", &config).unwrap();
+
+        assert_eq!(profile.run, "python3 {source}");
+        assert!(find_profile("cobol", &config).is_err());
+    }
+
+    #[test]
+    fn test_user_profile_overrides_builtin() {
+        let config: Config = toml::from_str(
+            r#"
+            [profiles.python]
+            source = "main.py"
+            run = "pypy3 main.py"
+            "#,
+        )
+        .unwrap();
+
+        let resolved = resolve_command(None, Some("python".to_string()), &config).unwrap();
+
+        assert_eq!(resolved.run, "pypy3 main.py");
+    }
+
+    #[test]
+    fn test_resolve_command_prefers_argument() {
+        let config: Config = toml::from_str(
+            r#"
+            [judge]
+            command = "./main"
+            "#,
+        )
+        .unwrap();
+
+        let resolved = resolve_command(Some("python3 main.py".to_string()), None, &config).unwrap();
+        assert_eq!(resolved.run, "python3 main.py");
+
+        let resolved = resolve_command(None, None, &config).unwrap();
+        assert_eq!(resolved.run, "./main");
+    }
+
+    #[test]
+    fn test_profile_build_uses_source() {
+        let config = Config::default();
+        let resolved = resolve_command(None, Some("c".to_string()), &config).unwrap();
+
+        assert_eq!(resolved.build.as_deref(), Some("gcc -O2 -o main main.c"));
+        assert_eq!(resolved.run, "./main");
+    }
+
+    #[test]
+    fn test_profile_for_file() {
+        let config = Config::default();
+
+        assert_eq!(
+            profile_for_file("solution.py", &config).as_deref(),
+            Some("python")
+        );
+        assert_eq!(
+            profile_for_file("src/Main.JAVA", &config).as_deref(),
+            Some("java")
+        );
+        assert_eq!(profile_for_file("notes.txt", &config), None);
+    }
+
+    #[test]
+    fn test_is_launcher_script() {
+        assert!(is_launcher_script("#!/bin/bash\n\nrlr j \"./main\"\n"));
+        assert!(is_launcher_script(
+            "cd ..\nrlr judge ./sample/target/release/sample"
+        ));
+        assert!(!is_launcher_script(
+            "#!/bin/bash\n# rlr judge\npython3 main.py\n"
+        ));
+    }
+}
@@ -0,0 +1,283 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Error,
+    literal::{Reader, Value, parse_value},
+    parser::ValueType,
+};
+
+/// How the arguments of a case are written to the `.in` file, i.e. what the
+/// program reads from stdin. It is chosen at download and saved in
+/// `problem.toml`, so that the stubs and `judge --function` read it back.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum InputLayout {
+    /// Every argument on one line, separated by spaces: `[1,2] "a b"`.
+    #[default]
+    Line,
+    /// One argument per line, strings without quotes.
+    Lines,
+    /// The competitive programming style: an array is its length on one line
+    /// followed by its elements separated by spaces on the next line, strings
+    /// are written without quotes.
+    Counted,
+    /// One JSON value per line.
+    Json,
+}
+
+impl InputLayout {
+    pub fn format(&self, values: &[Value]) -> String {
+        match self {
+            InputLayout::Line => {
+                format_vec_str(&values.iter().map(|v| v.to_string()).collect::<Vec<_>>())
+            }
+            InputLayout::Lines => lines(values.iter().map(raw_text)),
+            InputLayout::Counted => {
+                let mut out = Vec::new();
+                for value in values {
+                    write_counted(value, &mut out);
+                }
+                lines(out.into_iter())
+            }
+            InputLayout::Json => lines(values.iter().map(|v| v.to_json().to_string())),
+        }
+    }
+
+    /// Reads back what `format` wrote. Strings without quotes cannot be told
+    /// from other values, so `types` (the argument types, when known) decide.
+    pub fn parse(&self, text: &str, types: &[ValueType]) -> Result<Vec<Value>, Error> {
+        let type_of = |i: usize| types.get(i).unwrap_or(&ValueType::Unknown);
+
+        match self {
+            InputLayout::Line => {
+                let mut reader = Reader::new(text);
+                let mut values = Vec::new();
+                reader.skip_whitespace();
+                while !reader.rest().is_empty() {
+                    values.push(reader.value()?);
+                    reader.skip_whitespace();
+                }
+                Ok(values)
+            }
+            InputLayout::Lines => Ok(text
+                .lines()
+                .enumerate()
+                .map(|(i, line)| scalar(line, type_of(i)))
+                .collect()),
+            // without types, the arrays cannot be told apart, so every line is an argument
+            InputLayout::Counted if types.is_empty() => Ok(text
+                .lines()
+                .map(|line| scalar(line, &ValueType::Unknown))
+                .collect()),
+            InputLayout::Counted => {
+                let mut lines = text.lines();
+                let mut values = Vec::new();
+                for t in types {
+                    match read_counted(&mut lines, t)? {
+                        Some(value) => values.push(value),
+                        None => break,
+                    }
+                }
+                Ok(values)
+            }
+            InputLayout::Json => text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| {
+                    serde_json::from_str(line)
+                        .ok()
+                        .as_ref()
+                        .and_then(Value::from_json)
+                        .ok_or(Error::Literal(format!("invalid JSON: {}", line)))
+                })
+                .collect(),
+        }
+    }
+
+    /// How deep the arrays of each argument are, e.g. 2 for a 2D array. The
+    /// stubs read the counted layout with it.
+    pub fn depth(t: &ValueType) -> usize {
+        match t {
+            ValueType::Array(e) => 1 + Self::depth(e),
+            _ => 0,
+        }
+    }
+}
+
+/// Rewrites the input of a case for `judge --function`, whose harness reads
+/// one JSON argument per line whatever layout the `.in` files have.
+#[derive(Debug, Clone)]
+pub struct InputConversion {
+    pub from: InputLayout,
+    pub arg_types: Vec<ValueType>,
+}
+
+impl InputConversion {
+    pub fn apply(&self, text: &str) -> Result<String, Error> {
+        Ok(InputLayout::Json.format(&self.from.parse(text, &self.arg_types)?))
+    }
+}
+
+fn format_vec_str(s: &[String]) -> String {
+    s.iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn lines(items: impl Iterator<Item = String>) -> String {
+    items.map(|line| line + "\n").collect()
+}
+
+/// Strings without quotes, everything else as a literal.
+fn raw_text(value: &Value) -> String {
+    match value {
+        Value::Str(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+fn write_counted(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Array(items) => {
+            out.push(items.len().to_string());
+            if items.iter().any(|item| matches!(item, Value::Array(_))) {
+                for item in items {
+                    write_counted(item, out);
+                }
+            } else {
+                out.push(items.iter().map(raw_text).collect::<Vec<_>>().join(" "));
+            }
+        }
+        _ => out.push(raw_text(value)),
+    }
+}
+
+fn read_counted<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    t: &ValueType,
+) -> Result<Option<Value>, Error> {
+    let Some(line) = lines.next() else {
+        return Ok(None);
+    };
+
+    let ValueType::Array(e) = t else {
+        return Ok(Some(scalar(line, t)));
+    };
+    let count = line
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| Error::Literal(format!("expected the length of an array: {}", line)))?;
+
+    let items = if matches!(**e, ValueType::Array(_)) {
+        let mut items = Vec::new();
+        for _ in 0..count {
+            items.push(read_counted(lines, e)?.ok_or(Error::Literal(
+                "unexpected end of input in an array".to_string(),
+            ))?);
+        }
+        items
+    } else {
+        lines
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(|token| scalar(token, e))
+            .collect()
+    };
+    Ok(Some(Value::Array(items)))
+}
+
+/// A value written without quotes. A string argument keeps the text as is.
+fn scalar(text: &str, t: &ValueType) -> Value {
+    match t {
+        ValueType::Str => Value::Str(text.to_string()),
+        _ => parse_value(text).unwrap_or(Value::Str(text.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> Vec<Value> {
+        vec![
+            Value::Array(vec![
+                Value::Array(vec![Value::Int(1), Value::Int(2)]),
+                Value::Array(vec![]),
+            ]),
+            Value::Str("a b".to_string()),
+            Value::Bool(true),
+        ]
+    }
+
+    fn types() -> Vec<ValueType> {
+        vec![
+            ValueType::Array(Box::new(ValueType::Array(Box::new(ValueType::Int)))),
+            ValueType::Str,
+            ValueType::Bool,
+        ]
+    }
+
+    #[test]
+    fn test_format_vec_str() {
+        let v = vec!["1".to_string(), "2".to_string(), "3".to_string()];
+
+        let actual = format_vec_str(&v);
+        let expected = "1 2 3".to_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_format_layouts() {
+        assert_eq!(
+            InputLayout::Line.format(&values()),
+            r#"[[1,2],[]] "a b" true"#
+        );
+        assert_eq!(
+            InputLayout::Lines.format(&values()),
+            "[[1,2],[]]\na b\ntrue\n"
+        );
+        assert_eq!(
+            InputLayout::Counted.format(&values()),
+            "2\n2\n1 2\n0\n\na b\ntrue\n"
+        );
+        assert_eq!(
+            InputLayout::Json.format(&values()),
+            "[[1,2],[]]\n\"a b\"\ntrue\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_layouts_round_trip() {
+        for layout in [
+            InputLayout::Line,
+            InputLayout::Lines,
+            InputLayout::Counted,
+            InputLayout::Json,
+        ] {
+            let text = layout.format(&values());
+
+            assert_eq!(
+                layout.parse(&text, &types()).unwrap(),
+                values(),
+                "{:?}",
+                layout
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_without_types() {
+        assert_eq!(
+            InputLayout::Lines.parse("3\nhello world\n", &[]).unwrap(),
+            [Value::Int(3), Value::Str("hello world".to_string())]
+        );
+        assert_eq!(
+            InputLayout::Counted.parse("3\nx\n", &[]).unwrap(),
+            [Value::Int(3), Value::Str("x".to_string())]
+        );
+    }
+}
@@ -0,0 +1,468 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::error::Error;
+
+/// A value written in the RecursionCS literal syntax, e.g. an argument in
+/// `getLowestTemperature(3, 2)` or `isPalindrome("a,b")`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Array(Vec<Value>),
+}
+
+impl fmt::Display for Value {
+    /// Writes the value back in the literal syntax, without spaces in arrays.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Str(s) => {
+                f.write_str("\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' => f.write_str("\\\"")?,
+                        '\\' => f.write_str("\\\\")?,
+                        '\n' => f.write_str("\\n")?,
+                        '\t' => f.write_str("\\t")?,
+                        '\r' => f.write_str("\\r")?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                f.write_str("\"")
+            }
+            Value::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Converts JSON such as a `data-case` attribute. Objects have no literal
+    /// form, so they give `None`.
+    pub fn from_json(json: &serde_json::Value) -> Option<Self> {
+        Some(match json {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => Value::Float(n.as_f64()?),
+            },
+            serde_json::Value::String(s) => Value::Str(s.clone()),
+            serde_json::Value::Array(items) => {
+                Value::Array(items.iter().map(Value::from_json).collect::<Option<_>>()?)
+            }
+            serde_json::Value::Object(_) => return None,
+        })
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::Value::Bool(*b),
+            Value::Int(i) => serde_json::Value::from(*i),
+            Value::Float(x) => serde_json::Value::from(*x),
+            Value::Str(s) => serde_json::Value::String(s.clone()),
+            Value::Array(items) => items.iter().map(Value::to_json).collect(),
+        }
+    }
+}
+
+/// How a language prints values, for reading the output of a program. Every
+/// preset also reads the RecursionCS literals (`true`, `null`, `"a"`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// The spellings of every preset but Java's bare strings and C++'s `1`/`0`.
+    #[default]
+    Any,
+    /// Only the RecursionCS literals.
+    Strict,
+    /// `True`, `False`, `None` and single quoted strings.
+    Python,
+    /// Single quoted strings, as `console.log` prints them in arrays.
+    Javascript,
+    /// `nil` and single quoted strings.
+    Ruby,
+    /// Strings without quotes in arrays, as `Arrays.toString` prints them.
+    Java,
+    /// `1` and `0` for `true` and `false`, as `std::cout` prints them.
+    Cpp,
+}
+
+impl Preset {
+    fn word(&self, word: &str) -> Option<Value> {
+        use Preset::*;
+        match (self, word) {
+            (_, "true") => Some(Value::Bool(true)),
+            (_, "false") => Some(Value::Bool(false)),
+            (_, "null") => Some(Value::Null),
+            (Any | Python, "True") => Some(Value::Bool(true)),
+            (Any | Python, "False") => Some(Value::Bool(false)),
+            (Any | Python, "None") | (Any | Ruby, "nil") => Some(Value::Null),
+            _ => None,
+        }
+    }
+
+    fn is_quote(&self, c: char) -> bool {
+        match self {
+            Preset::Any | Preset::Python | Preset::Javascript | Preset::Ruby => {
+                c == '"' || c == '\''
+            }
+            Preset::Strict | Preset::Java | Preset::Cpp => c == '"',
+        }
+    }
+
+    fn bare_strings(&self) -> bool {
+        *self == Preset::Java
+    }
+
+    /// Whether `1` and `0` are read as booleans when a boolean is expected.
+    pub fn numeric_bools(&self) -> bool {
+        *self == Preset::Cpp
+    }
+}
+
+/// Reads literals from text one token at a time.
+pub struct Reader {
+    chars: Vec<char>,
+    pos: usize,
+    preset: Preset,
+}
+
+impl Reader {
+    pub fn new(text: &str) -> Self {
+        Self::with_preset(text, Preset::Any)
+    }
+
+    pub fn with_preset(text: &str, preset: Preset) -> Self {
+        Self {
+            chars: text.chars().collect(),
+            pos: 0,
+            preset,
+        }
+    }
+
+    /// The text that has not been read yet.
+    pub fn rest(&self) -> String {
+        self.chars[self.pos..].iter().collect()
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    /// Consumes `expected` after optional whitespace.
+    pub fn expect(&mut self, expected: &str) -> Result<(), Error> {
+        self.skip_whitespace();
+        for c in expected.chars() {
+            if self.peek() != Some(c) {
+                return Err(self.error(&format!("expected {:?}", expected)));
+            }
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    fn error(&self, message: &str) -> Error {
+        let context = self.chars.iter().collect::<String>();
+        Error::Literal(format!(
+            "{} at column {}: {}",
+            message,
+            self.pos + 1,
+            context.trim()
+        ))
+    }
+
+    pub fn value(&mut self) -> Result<Value, Error> {
+        self.skip_whitespace();
+        if !self.preset.bare_strings() {
+            return self.literal();
+        }
+
+        let start = self.pos;
+        self.literal().or_else(|_| {
+            self.pos = start;
+            self.bare_string()
+        })
+    }
+
+    fn literal(&mut self) -> Result<Value, Error> {
+        match self.peek() {
+            Some(c) if self.preset.is_quote(c) => self.string(),
+            Some('[') => {
+                self.pos += 1;
+                Ok(Value::Array(self.list(Some(']'))?))
+            }
+            Some(c) if c == '-' || c == '+' || c.is_ascii_digit() => self.number(),
+            Some(c) if c.is_alphabetic() => self.word(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    /// Reads comma separated values up to `close`, consuming it, or up to the
+    /// end of the text when `close` is `None`. The list may be empty.
+    pub fn list(&mut self, close: Option<char>) -> Result<Vec<Value>, Error> {
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == close {
+            self.pos += 1;
+            return Ok(items);
+        }
+
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                c if c == close => return Ok(items),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("expected \",\" or the end of the list"));
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<Value, Error> {
+        let quote = self.next();
+        let mut s = String::new();
+
+        loop {
+            match self.next() {
+                None => return Err(self.error("unterminated string")),
+                Some(c) if Some(c) == quote => return Ok(Value::Str(s)),
+                Some('\\') => {
+                    let escaped = match self.next() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('0') => '\0',
+                        Some('u') => self.unicode_escape()?,
+                        Some(c) => c,
+                        None => return Err(self.error("unterminated string")),
+                    };
+                    s.push(escaped);
+                }
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, Error> {
+        let end = self.pos + 4;
+        let hex = self
+            .chars
+            .get(self.pos..end)
+            .ok_or(self.error("invalid unicode escape"))?
+            .iter()
+            .collect::<String>();
+        self.pos = end;
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or(self.error("invalid unicode escape"))
+    }
+
+    fn number(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.'))
+        {
+            self.pos += 1;
+        }
+        let text = self.chars[start..self.pos].iter().collect::<String>();
+
+        if let Ok(i) = text.parse::<i64>() {
+            Ok(Value::Int(i))
+        } else if let Ok(x) = text.parse::<f64>() {
+            Ok(Value::Float(x))
+        } else {
+            self.pos = start;
+            Err(self.error("invalid number"))
+        }
+    }
+
+    fn word(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.pos += 1;
+        }
+        let word = self.chars[start..self.pos].iter().collect::<String>();
+
+        self.preset.word(&word).ok_or_else(|| {
+            self.pos = start;
+            self.error("unknown literal")
+        })
+    }
+
+    /// A string without quotes, up to the end of the array item.
+    fn bare_string(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c != ',' && c != ']') {
+            self.pos += 1;
+        }
+        let text = self.chars[start..self.pos].iter().collect::<String>();
+        if text.trim().is_empty() {
+            return Err(self.error("expected a value"));
+        }
+        Ok(Value::Str(text.trim().to_string()))
+    }
+}
+
+/// Parses text holding exactly one value.
+pub fn parse_value(text: &str) -> Result<Value, Error> {
+    parse_value_with(text, Preset::Any)
+}
+
+/// Parses text holding exactly one value, written the way `preset` prints it.
+pub fn parse_value_with(text: &str, preset: Preset) -> Result<Value, Error> {
+    let mut reader = Reader::with_preset(text, preset);
+    let value = reader.value()?;
+    reader.skip_whitespace();
+    if !reader.rest().is_empty() {
+        return Err(reader.error("unexpected text after the value"));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<Value> {
+        Reader::new(text).list(None).unwrap()
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(
+            args("3, -8, 1.5, 1e3"),
+            [
+                Value::Int(3),
+                Value::Int(-8),
+                Value::Float(1.5),
+                Value::Float(1000.0)
+            ]
+        );
+        assert!(parse_value("1.2.3").is_err());
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(
+            args(r#""a,b", 'c)d', "say \"hi\"\n", "あ""#),
+            [
+                Value::Str("a,b".to_string()),
+                Value::Str("c)d".to_string()),
+                Value::Str("say \"hi\"\n".to_string()),
+                Value::Str("あ".to_string())
+            ]
+        );
+        assert!(parse_value("\"abc").is_err());
+    }
+
+    #[test]
+    fn test_bools_and_null() {
+        assert_eq!(
+            args("true, false, null"),
+            [Value::Bool(true), Value::Bool(false), Value::Null]
+        );
+        assert!(parse_value("maybe").is_err());
+    }
+
+    #[test]
+    fn test_nested_arrays() {
+        assert_eq!(
+            args("[1,2],[3,[4, \"]\"]], []"),
+            [
+                Value::Array(vec![Value::Int(1), Value::Int(2)]),
+                Value::Array(vec![
+                    Value::Int(3),
+                    Value::Array(vec![Value::Int(4), Value::Str("]".to_string())])
+                ]),
+                Value::Array(vec![])
+            ]
+        );
+        assert!(parse_value("[1,2").is_err());
+    }
+
+    #[test]
+    fn test_empty_list() {
+        assert_eq!(args("  "), []);
+        assert_eq!(Reader::new(")").list(Some(')')).unwrap(), []);
+    }
+
+    #[test]
+    fn test_list_stops_at_close() {
+        let mut reader = Reader::new("1, \"x)\") --> 2");
+
+        assert_eq!(
+            reader.list(Some(')')).unwrap(),
+            [Value::Int(1), Value::Str("x)".to_string())]
+        );
+        assert_eq!(reader.rest(), " --> 2");
+    }
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(
+            parse_value_with("[True, 'a', None]", Preset::Python).unwrap(),
+            Value::Array(vec![
+                Value::Bool(true),
+                Value::Str("a".to_string()),
+                Value::Null
+            ])
+        );
+        assert!(parse_value_with("True", Preset::Strict).is_err());
+        assert_eq!(
+            parse_value_with("'a'", Preset::Java).unwrap(),
+            Value::Str("'a'".to_string())
+        );
+        assert_eq!(
+            parse_value_with("[a b, 2, c]", Preset::Java).unwrap(),
+            Value::Array(vec![
+                Value::Str("a b".to_string()),
+                Value::Int(2),
+                Value::Str("c".to_string())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let value = parse_value(r#"[1, 2.5, "a\"b", [true, null]]"#).unwrap();
+
+        assert_eq!(value.to_string(), r#"[1,2.5,"a\"b",[true,null]]"#);
+    }
+}
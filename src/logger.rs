@@ -0,0 +1,181 @@
+use colored::Colorize;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::Instant,
+};
+
+use crate::{
+    error::Error,
+    messages::{ERROR_LABEL, INFO_LABEL, NETWORK_LABEL},
+};
+
+/// Target used for request/response lines, shown from `-v` on.
+pub const NETWORK: &str = "network";
+
+const REDACTED_HEADERS: [&str; 5] = [
+    "cookie",
+    "set-cookie",
+    "authorization",
+    "x-xsrf-token",
+    "x-csrf-token",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+    Debug,
+}
+
+impl Verbosity {
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::Debug,
+        }
+    }
+
+    fn level_filter(&self) -> LevelFilter {
+        match self {
+            Verbosity::Quiet => LevelFilter::Warn,
+            Verbosity::Normal | Verbosity::Verbose => LevelFilter::Info,
+            Verbosity::Debug => LevelFilter::Debug,
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.level() > self.level_filter() {
+            return false;
+        }
+        metadata.target() != NETWORK || *self >= Verbosity::Verbose
+    }
+}
+
+struct Logger {
+    verbosity: Verbosity,
+    log_file: Option<Mutex<File>>,
+    started: Instant,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.log_file.is_some() || self.verbosity.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.verbosity.enabled(record.metadata()) {
+            eprintln!("[{}] {}", console_label(record), record.args());
+        }
+
+        if let Some(file) = &self.log_file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = writeln!(
+                file,
+                "[{:>10.3}s] [{:<5}] [{}] {}",
+                self.started.elapsed().as_secs_f64(),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.log_file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = file.flush();
+        }
+    }
+}
+
+fn console_label(record: &Record) -> String {
+    match record.level() {
+        Level::Error => ERROR_LABEL.to_string(),
+        Level::Warn => "WARN".yellow().to_string(),
+        Level::Info if record.target() == NETWORK => NETWORK_LABEL.to_string(),
+        Level::Info => INFO_LABEL.to_string(),
+        Level::Debug => "DEBUG".cyan().to_string(),
+        Level::Trace => "TRACE".dimmed().to_string(),
+    }
+}
+
+/// Installs the global logger. Everything down to debug level is written to
+/// `log_file` regardless of the console verbosity.
+pub fn init_logger(verbosity: Verbosity, log_file: Option<&Path>) -> Result<(), Error> {
+    let log_file = match log_file {
+        Some(path) => Some(Mutex::new(
+            OpenOptions::new().create(true).append(true).open(path)?,
+        )),
+        None => None,
+    };
+
+    let max_level = if log_file.is_some() {
+        LevelFilter::Debug
+    } else {
+        verbosity.level_filter()
+    };
+
+    let logger = Logger {
+        verbosity,
+        log_file,
+        started: Instant::now(),
+    };
+
+    log::set_boxed_logger(Box::new(logger))
+        .map_err(|e| Error::Internal(format!("Failed to set logger: {}", e)))?;
+    log::set_max_level(max_level);
+
+    Ok(())
+}
+
+/// Hides the values of headers that carry credentials.
+pub fn redact_header(name: &str, value: &str) -> String {
+    if REDACTED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+        "<redacted>".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_from_flags() {
+        assert_eq!(Verbosity::from_flags(true, 2), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, 2), Verbosity::Debug);
+    }
+
+    #[test]
+    fn test_network_target_needs_verbose() {
+        let network = Metadata::builder()
+            .level(Level::Info)
+            .target(NETWORK)
+            .build();
+        let info = Metadata::builder().level(Level::Info).target("rlr").build();
+
+        assert!(!Verbosity::Normal.enabled(&network));
+        assert!(Verbosity::Normal.enabled(&info));
+        assert!(Verbosity::Verbose.enabled(&network));
+        assert!(!Verbosity::Quiet.enabled(&info));
+    }
+
+    #[test]
+    fn test_redact_header() {
+        assert_eq!(redact_header("Cookie", "a=b"), "<redacted>");
+        assert_eq!(redact_header("set-cookie", "a=b"), "<redacted>");
+        assert_eq!(redact_header("Content-Type", "text/html"), "text/html");
+    }
+}
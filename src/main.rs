@@ -1,106 +1,506 @@
 use std::io;
 use std::io::Write;
+use std::path::PathBuf;
 
+use cache::CacheMode;
+use catalog::{ListOptions, SortKey, list};
+use clap::ArgAction;
+use clap::Args;
+use clap::CommandFactory;
+use clap::FromArgMatches;
 use clap::Parser;
 use clap::Subcommand;
+use config::{Config, config_get, config_list, config_set};
 use error::Error;
 use error::handle_error;
+use extract::extract;
 use file::cookie_path;
-use judge::judge;
+use harness::function_command;
+use i18n::{Lang, Msg, init_lang, lang_from_args, localize_command, tr};
+use judge::{JudgeOptions, RunInput, RunOptions, case_file_path, judge, run_once};
+use language::{ResolvedCommand, resolve_command};
+use layout::InputLayout;
+use logger::{Verbosity, init_logger};
+use messages::{ColorChoice, INFO_LABEL, init_output};
 use regex::Regex;
-use request::download;
 use request::initial_auth;
+use request::{DownloadOptions, download, import};
+use submit::{SubmitOptions, submit};
+use template::{template_add, template_list, template_remove};
 
+mod cache;
+mod catalog;
+mod config;
 mod error;
+mod extract;
 mod file;
+mod harness;
+mod i18n;
 mod judge;
+mod language;
+mod layout;
+mod literal;
+mod logger;
+mod markdown;
 mod messages;
+#[cfg(test)]
+mod mock_server;
+mod network;
 mod parser;
+mod problem;
 mod request;
+mod site;
+mod stub;
+mod submit;
+mod template;
 
 #[derive(Parser)]
-#[command(
-    version,
-    about = "rlr はローカルでRecursionの問題を実行するためのツールです",
-    long_about = "rlrはローカルでRecursionの問題を実行するためのツールです。\n\
-    rlrの簡単な使い方について説明します。\n\
-    詳細はGithubページ(https://github.com/kip2/rlr)を参照して下さい。\n\
-    \n\
-    1. loginオプションを使用して、Recursionへの初回ログインを行って下さい。\n\
-    2. downloadオプションで、取得したい問題ページのURLを引数に与え、テストケースの値を取得して下さい。\n\
-    3. 好きな言語・好きなエディタで問題を解くコードを書いてください。\n\
-    4. 3で書いたコードをシェルから実行するコマンドを用意し、コマンド実行文字列として与えて実行して下さい。 ",
-    override_usage = "\
-    \n
-    login:    rlr login または rlr l
-    donwload: rlr download <URL> または rlr d <URL>
-    judge:    rlr judge <COMMAND> または rlr j <COMMAND>"
-)]
+#[command(version)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = ColorChoice::Auto
+    )]
+    color: ColorChoice,
+
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count
+    )]
+    verbose: u8,
+
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    #[arg(long, global = true, value_enum)]
+    lang: Option<Lang>,
+
+    #[command(flatten)]
+    network: NetworkArgs,
+}
+
+/// Connection options overriding `[network]`, for the commands that go online.
+#[derive(Args)]
+struct NetworkArgs {
+    #[arg(long, global = true, value_name = "URL")]
+    proxy: Option<String>,
+
+    #[arg(long, global = true, value_name = "FILE")]
+    ca_cert: Vec<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        conflicts_with_all = ["insecure", "ca_cert"]
+    )]
+    pin_cert: Option<String>,
+
+    #[arg(long, global = true)]
+    insecure: bool,
+}
+
+impl NetworkArgs {
+    /// The config, with these options applied over its `[network]`.
+    fn load_config(&self) -> Result<Config, Error> {
+        let mut config = Config::load()?;
+        let network = &mut config.network;
+        if self.proxy.is_some() {
+            network.proxy = self.proxy.clone();
+        }
+        if !self.ca_cert.is_empty() {
+            network
+                .ca_certs
+                .get_or_insert_default()
+                .extend(self.ca_cert.iter().cloned());
+        }
+        if self.pin_cert.is_some() {
+            network.pinned_cert = self.pin_cert.clone();
+        }
+        if self.insecure {
+            network.insecure = Some(true);
+        }
+        Ok(config)
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    #[command(alias = "l", about = "Recursionへのログイン処理を行います。")]
+    #[command(alias = "l")]
     Login,
 
-    #[command(alias = "d", about = "指定したurlのテストケースをダウンロードします。")]
+    #[command(alias = "d")]
     Download(DownloadArgs),
 
-    #[command(
-        alias = "j",
-        about = "カレントディレクトリにあるtestcaseディレクトリに対して、指定されたコマンドを使用してテストを実行します"
-    )]
+    Import(ImportArgs),
+
+    #[command(alias = "ls")]
+    List(ListArgs),
+
+    #[command(alias = "j")]
     Judge(JudgeArgs),
 
-    #[command(about = "Cookieファイルの保存パスを取得します。")]
+    #[command(alias = "r")]
+    Run(RunArgs),
+
+    Extract(ExtractArgs),
+
+    #[command(alias = "s")]
+    Submit(SubmitArgs),
+
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
+    #[command(subcommand)]
+    Template(TemplateCommands),
+
     CookiePath,
 }
 
-#[derive(Parser)]
+#[derive(Subcommand)]
+enum ConfigCommands {
+    Get {
+        key: String,
+    },
+    Set {
+        key: String,
+        value: String,
+
+        #[arg(long)]
+        global: bool,
+    },
+    List,
+}
+
+#[derive(Subcommand)]
+enum TemplateCommands {
+    List,
+    Add {
+        name: String,
+        dir: String,
+
+        #[arg(long)]
+        global: bool,
+    },
+    Remove {
+        name: String,
+
+        #[arg(long)]
+        global: bool,
+    },
+}
+
+#[derive(Args)]
 struct JudgeArgs {
-    judge_command: String,
+    judge_command: Option<String>,
+
+    #[arg(long, conflicts_with = "judge_command")]
+    profile: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        num_args = 0..=1,
+        default_missing_value = "",
+        conflicts_with = "judge_command"
+    )]
+    function: Option<String>,
+
+    #[arg(long)]
+    summary_only: bool,
 }
 
-#[derive(Parser)]
+#[derive(Args)]
+struct RunArgs {
+    run_command: Option<String>,
+
+    #[arg(long, conflicts_with = "run_command")]
+    profile: Option<String>,
+
+    #[arg(long, value_name = "N", conflicts_with = "input")]
+    case: Option<usize>,
+
+    #[arg(long, value_name = "FILE")]
+    input: Option<String>,
+
+    #[arg(long, value_name = "FILE", conflicts_with = "diff")]
+    expected: Option<String>,
+
+    #[arg(long, requires = "case")]
+    diff: bool,
+}
+
+#[derive(Args)]
+struct ExtractArgs {
+    file: String,
+
+    #[arg(long, value_name = "NAME")]
+    function: Option<String>,
+}
+
+#[derive(Args)]
+struct SubmitArgs {
+    file: Option<String>,
+
+    #[arg(long, value_name = "N")]
+    id: Option<u32>,
+
+    #[arg(long, value_name = "NAME")]
+    language: Option<String>,
+
+    #[arg(long)]
+    whole_file: bool,
+
+    #[arg(long)]
+    no_wait: bool,
+}
+
+#[derive(Args)]
 struct DownloadArgs {
-    url: String,
+    #[arg(required_unless_present = "from_file")]
+    url: Vec<String>,
+
+    #[arg(long, value_name = "FILE")]
+    from_file: Option<String>,
+
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
+
+    #[arg(long, conflicts_with = "refresh")]
+    offline: bool,
+
+    #[arg(long)]
+    refresh: bool,
+
+    #[command(flatten)]
+    setup: SetupArgs,
 }
 
-fn main() {
-    #[cfg(windows)]
-    {
-        use messages::enable_ansi_support;
-        enable_ansi_support();
+#[derive(Args)]
+struct ImportArgs {
+    file: String,
+
+    #[arg(long, value_name = "N")]
+    id: Option<u32>,
+
+    #[command(flatten)]
+    setup: SetupArgs,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    #[arg(long)]
+    offline: bool,
+
+    #[arg(long, value_name = "NAME")]
+    category: Option<String>,
+
+    #[arg(long, value_name = "LEVEL")]
+    difficulty: Option<String>,
+
+    #[arg(long, conflicts_with = "unsolved")]
+    solved: bool,
+
+    #[arg(long)]
+    unsolved: bool,
+
+    #[arg(long, value_name = "TEXT")]
+    search: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = SortKey::Id)]
+    sort: SortKey,
+
+    #[arg(long)]
+    reverse: bool,
+}
+
+/// Options shared by `download` and `import` for setting up the problem directory.
+#[derive(Args)]
+struct SetupArgs {
+    #[arg(short, long, value_name = "NAME")]
+    template: Option<String>,
+
+    #[arg(long, value_name = "LANG")]
+    stub: Option<String>,
+
+    #[arg(long, value_enum)]
+    layout: Option<InputLayout>,
+}
+
+impl From<SetupArgs> for DownloadOptions {
+    fn from(args: SetupArgs) -> Self {
+        Self {
+            template: args.template,
+            stub: args.stub,
+            layout: args.layout,
+            jobs: None,
+            cache: CacheMode::default(),
+        }
     }
+}
 
-    if let Err(e) = run() {
+fn main() {
+    init_lang(lang_from_args(std::env::args()));
+
+    let matches = localize_command(Cli::command()).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    init_output(cli.color, cli.ascii);
+
+    if let Err(e) = run(cli) {
         handle_error(e);
     }
 }
 
-fn run() -> Result<(), Error> {
-    let cli = Cli::parse();
+fn run(cli: Cli) -> Result<(), Error> {
+    let verbosity = Verbosity::from_flags(cli.quiet, cli.verbose);
+    init_logger(verbosity, cli.log_file.as_deref())?;
 
     match cli.command {
-        Commands::Download(args) => download(&args.url)?,
-        Commands::Judge(args) => judge(&args.judge_command)?,
-        Commands::Login => login()?,
+        Commands::Download(args) => {
+            let options = DownloadOptions {
+                jobs: args.jobs,
+                cache: match (args.offline, args.refresh) {
+                    (true, _) => CacheMode::Offline,
+                    (_, true) => CacheMode::Refresh,
+                    _ => CacheMode::Revalidate,
+                },
+                ..DownloadOptions::from(args.setup)
+            };
+            download(
+                &args.url,
+                args.from_file.as_deref(),
+                &options,
+                &cli.network.load_config()?,
+            )?
+        }
+        Commands::Import(args) => {
+            let options = DownloadOptions::from(args.setup);
+            import(&args.file, args.id, &options, &Config::load()?)?
+        }
+        Commands::List(args) => {
+            let options = ListOptions {
+                offline: args.offline,
+                category: args.category,
+                difficulty: args.difficulty,
+                solved: match (args.solved, args.unsolved) {
+                    (true, _) => Some(true),
+                    (_, true) => Some(false),
+                    _ => None,
+                },
+                search: args.search,
+                sort: args.sort,
+                reverse: args.reverse,
+            };
+            list(&options, &cli.network.load_config()?)?
+        }
+        Commands::Judge(args) => {
+            let config = Config::load()?;
+            let (command, harness) = match args.function {
+                Some(name) => {
+                    let name = Some(name).filter(|n| !n.is_empty());
+                    let (command, harness) = function_command(name, args.profile, &config)?;
+                    (command, Some(harness))
+                }
+                None => (
+                    resolve_command(args.judge_command, args.profile, &config)?,
+                    None,
+                ),
+            };
+            print_command(&command);
+            let options = JudgeOptions {
+                summary_only: args.summary_only,
+                build: command.build.clone(),
+                timeout: config.timeout(),
+                comparator: config.comparator(),
+                preset: config.preset(),
+                testcase_dir: config.testcase_dir().to_string(),
+                input_conversion: harness.as_ref().map(|harness| harness.input.clone()),
+            };
+            judge(&command.run, &options)?
+        }
+        Commands::Run(args) => {
+            let input = match (args.case, args.input) {
+                (Some(n), _) => RunInput::Case(n),
+                (None, Some(path)) => RunInput::File(path),
+                (None, None) => RunInput::Stdin,
+            };
+            let config = Config::load()?;
+            let command = resolve_command(args.run_command, args.profile, &config)?;
+            print_command(&command);
+            let expected = match args.case {
+                Some(n) if args.diff => Some(case_file_path(config.testcase_dir(), n, "out")),
+                _ => args.expected,
+            };
+            let options = RunOptions {
+                input,
+                expected,
+                build: command.build.clone(),
+                timeout: config.timeout(),
+                comparator: config.comparator(),
+                preset: config.preset(),
+                testcase_dir: config.testcase_dir().to_string(),
+            };
+            run_once(&command.run, &options)?
+        }
+        Commands::Extract(args) => extract(&args.file, args.function)?,
+        Commands::Submit(args) => {
+            let options = SubmitOptions {
+                file: args.file,
+                id: args.id.map(|id| id.to_string()),
+                language: args.language,
+                whole_file: args.whole_file,
+                wait: !args.no_wait,
+            };
+            submit(&options, &cli.network.load_config()?)?
+        }
+        Commands::Config(command) => match command {
+            ConfigCommands::Get { key } => config_get(&key)?,
+            ConfigCommands::Set { key, value, global } => config_set(&key, &value, global)?,
+            ConfigCommands::List => config_list()?,
+        },
+        Commands::Template(command) => match command {
+            TemplateCommands::List => template_list()?,
+            TemplateCommands::Add { name, dir, global } => template_add(&name, &dir, global)?,
+            TemplateCommands::Remove { name, global } => template_remove(&name, global)?,
+        },
+        Commands::Login => login(cli.network.load_config()?)?,
         Commands::CookiePath => cookie_path()?,
     }
 
     Ok(())
 }
 
-fn login() -> Result<(), Error> {
-    let email = prompt_email("Emailアドレス: ")?;
+fn print_command(command: &ResolvedCommand) {
+    println!(
+        "[{}] {}",
+        *INFO_LABEL,
+        tr!(Msg::CommandFrom, command.run, command.origin)
+    );
+}
+
+fn login(config: Config) -> Result<(), Error> {
+    let site = config.site()?;
+
+    let email = prompt_email(Msg::PromptEmail.text())?;
 
-    let password =
-        rpassword::prompt_password("Password: ").expect("パスワードの入力処理に失敗しました");
+    let password = rpassword::prompt_password(Msg::PromptPassword.text())
+        .unwrap_or_else(|_| panic!("{}", Msg::PasswordInputFailed));
 
-    initial_auth(&email, &password)?;
+    initial_auth(&email, &password, &site, &config.network())?;
     Ok(())
 }
 
@@ -110,7 +510,7 @@ fn prompt_email(prompt: &str) -> Result<String, Error> {
         print!("{}", prompt);
         io::stdout().flush()?;
         if let Err(e) = io::stdin().read_line(&mut email) {
-            eprintln!("入力に失敗しました: {}", e);
+            eprintln!("{}", tr!(Msg::InputFailed, e));
             continue;
         }
 
@@ -120,7 +520,7 @@ fn prompt_email(prompt: &str) -> Result<String, Error> {
             return Ok(email.to_string());
         }
 
-        println!("Emailの形式で入力して下さい。");
+        println!("{}", Msg::EmailFormat);
     }
 }
 
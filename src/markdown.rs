@@ -0,0 +1,238 @@
+use scraper::{ElementRef, Node};
+
+/// Stands for a `<br>` until the lines are trimmed, as two trailing spaces.
+const LINE_BREAK: char = '\u{E000}';
+
+/// Converts the HTML of a problem statement to Markdown, keeping headings,
+/// paragraphs, lists, tables, links, inline code and code blocks.
+pub fn html_to_markdown(element: ElementRef) -> String {
+    let mut out = String::new();
+    write_children(element, &mut out);
+    tidy(&out)
+}
+
+fn write_children(element: ElementRef, out: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(&collapse_whitespace(text)),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    write_element(child, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_element(element: ElementRef, out: &mut String) {
+    let name = element.value().name();
+    match name {
+        "script" | "style" | "button" | "nav" | "noscript" => {}
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..].parse::<usize>().unwrap_or(1);
+            out.push_str(&format!("\n\n{} ", "#".repeat(level)));
+            out.push_str(inline_text(element).trim());
+            out.push_str("\n\n");
+        }
+        "p" | "div" | "section" | "article" | "dl" => {
+            out.push_str("\n\n");
+            write_children(element, out);
+            out.push_str("\n\n");
+        }
+        "dt" => {
+            out.push_str("\n\n**");
+            out.push_str(inline_text(element).trim());
+            out.push_str("**\n");
+        }
+        "dd" => {
+            out.push_str(": ");
+            out.push_str(inline_text(element).trim());
+            out.push('\n');
+        }
+        "br" => {
+            out.push(LINE_BREAK);
+            out.push('\n');
+        }
+        "hr" => out.push_str("\n\n---\n\n"),
+        "pre" => {
+            let code = element.text().collect::<String>();
+            out.push_str("\n\n```\n");
+            out.push_str(code.trim_matches('\n'));
+            out.push_str("\n```\n\n");
+        }
+        "code" => {
+            let code = element.text().collect::<String>();
+            out.push_str(&format!("`{}`", code.trim()));
+        }
+        "strong" | "b" => {
+            out.push_str(&format!("**{}**", inline_text(element).trim()));
+        }
+        "em" | "i" => {
+            let text = inline_text(element);
+            if !text.trim().is_empty() {
+                out.push_str(&format!("*{}*", text.trim()));
+            }
+        }
+        "a" => {
+            let text = inline_text(element);
+            match element.value().attr("href") {
+                Some(href) if !href.starts_with('#') => {
+                    out.push_str(&format!("[{}]({})", text.trim(), href))
+                }
+                _ => out.push_str(&text),
+            }
+        }
+        "ul" | "ol" => {
+            out.push_str("\n\n");
+            let items = element
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|child| child.value().name() == "li");
+            for (i, item) in items.enumerate() {
+                let marker = if name == "ol" {
+                    format!("{}.", i + 1)
+                } else {
+                    "-".to_string()
+                };
+                out.push_str(&format!("{} {}\n", marker, inline_text(item).trim()));
+            }
+            out.push('\n');
+        }
+        "table" => write_table(element, out),
+        _ => write_children(element, out),
+    }
+}
+
+fn write_table(table: ElementRef, out: &mut String) {
+    let rows = table
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "tr")
+        .map(|row| {
+            row.children()
+                .filter_map(ElementRef::wrap)
+                .filter(|cell| matches!(cell.value().name(), "th" | "td"))
+                .map(|cell| inline_text(cell).trim().replace('|', "\\|"))
+                .collect::<Vec<_>>()
+        })
+        .filter(|cells| !cells.is_empty())
+        .collect::<Vec<_>>();
+
+    let Some(header) = rows.first() else {
+        return;
+    };
+    out.push_str("\n\n");
+    out.push_str(&format!("| {} |\n", header.join(" | ")));
+    out.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+    for row in &rows[1..] {
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    out.push('\n');
+}
+
+/// The Markdown of an element that should stay on one line.
+fn inline_text(element: ElementRef) -> String {
+    let mut out = String::new();
+    write_children(element, &mut out);
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::new();
+    let mut last_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_space {
+                result.push(' ');
+            }
+            last_space = true;
+        } else {
+            result.push(c);
+            last_space = false;
+        }
+    }
+    result
+}
+
+/// Trims every line outside code blocks and keeps at most one blank line in a row.
+fn tidy(markdown: &str) -> String {
+    let mut lines = Vec::<String>::new();
+    let mut in_code = false;
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            lines.push(line.trim().to_string());
+            continue;
+        }
+        if in_code {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let line = match line.trim().strip_suffix(LINE_BREAK) {
+            Some(line) => format!("{}  ", line.trim_end()),
+            None => line.trim().to_string(),
+        };
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    fn convert(html: &str) -> String {
+        let document = Html::parse_fragment(html);
+        let selector = Selector::parse("div").unwrap();
+        html_to_markdown(document.select(&selector).next().unwrap())
+    }
+
+    #[test]
+    fn test_paragraphs_and_inline() {
+        let actual = convert(
+            "<div><h4>問題</h4><p>最高気温 <code>high</code> を\n   <strong>返す</strong>。</p><p><a href=\"https://example.com\">link</a></p></div>",
+        );
+
+        assert_eq!(
+            actual,
+            "#### 問題\n\n最高気温 `high` を **返す**。\n\n[link](https://example.com)\n"
+        );
+    }
+
+    #[test]
+    fn test_lists_and_code_blocks() {
+        let actual = convert(
+            "<div><ul><li>1 &lt;= n</li><li>n &lt;= 10</li></ul><pre><code>function f() {\n    return 0;\n}</code></pre></div>",
+        );
+
+        assert_eq!(
+            actual,
+            "- 1 <= n\n- n <= 10\n\n```\nfunction f() {\n    return 0;\n}\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_line_break() {
+        assert_eq!(convert("<div><p>a <br>b </p></div>"), "a  \nb\n");
+    }
+
+    #[test]
+    fn test_table() {
+        let actual = convert(
+            "<div><table><tr><th>入力</th><th>出力</th></tr><tr><td>1</td><td>2</td></tr></table></div>",
+        );
+
+        assert_eq!(actual, "| 入力 | 出力 |\n| --- | --- |\n| 1 | 2 |\n");
+    }
+}
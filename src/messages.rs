@@ -1,5 +1,14 @@
+use crate::i18n::Msg;
+use clap::ValueEnum;
 use colored::Colorize;
 use once_cell::sync::Lazy;
+use std::{
+    io::IsTerminal,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use unicode_width::UnicodeWidthStr;
+
+static ASCII_ONLY: AtomicBool = AtomicBool::new(false);
 
 pub static SUCCESS_LABEL: Lazy<String> = Lazy::new(|| "SUCCESS".green().to_string());
 pub static FAILURE_LABEL: Lazy<String> = Lazy::new(|| "FAILURE".red().to_string());
@@ -8,13 +17,136 @@ pub static FAILED_LABEL: Lazy<String> = Lazy::new(|| "FAILED".red().to_string())
 pub static INFO_LABEL: Lazy<String> = Lazy::new(|| "INFO".blue().to_string());
 pub static NETWORK_LABEL: Lazy<String> = Lazy::new(|| "NETWORK".purple().to_string());
 pub static ERROR_LABEL: Lazy<String> = Lazy::new(|| "ERROR".yellow().to_string());
-pub static AC_LABEL: Lazy<String> = Lazy::new(|| "AC (Accepted)".green().to_string());
-pub static WA_LABEL: Lazy<String> = Lazy::new(|| "WA (Wrong Answer)".red().to_string());
-pub static RE_LABEL: Lazy<String> = Lazy::new(|| "RE (Runtime Error)".yellow().to_string());
-pub static TLE_LABEL: Lazy<String> = Lazy::new(|| "TLE (Time Limit Exceeded)".yellow().to_string());
+pub static AC_LABEL: Lazy<String> = Lazy::new(|| Msg::Accepted.text().green().to_string());
+pub static WA_LABEL: Lazy<String> = Lazy::new(|| Msg::WrongAnswer.text().red().to_string());
+pub static RE_LABEL: Lazy<String> = Lazy::new(|| Msg::RuntimeError.text().yellow().to_string());
+pub static TLE_LABEL: Lazy<String> = Lazy::new(|| Msg::TimeLimit.text().yellow().to_string());
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Characters used to draw tables, switched to plain ASCII with `--ascii`.
+pub struct BoxChars {
+    pub vertical: &'static str,
+    pub horizontal: &'static str,
+    pub cross: &'static str,
+}
+
+const UNICODE_BOX: BoxChars = BoxChars {
+    vertical: "│",
+    horizontal: "─",
+    cross: "┼",
+};
+
+const ASCII_BOX: BoxChars = BoxChars {
+    vertical: "|",
+    horizontal: "-",
+    cross: "+",
+};
+
+/// Must be called before any label is used, since the labels are colored
+/// once on first access.
+pub fn init_output(color: ColorChoice, ascii_only: bool) {
+    let no_color = std::env::var("NO_COLOR").ok();
+    let enabled = should_colorize(color, no_color.as_deref(), std::io::stdout().is_terminal());
+
+    colored::control::set_override(enabled);
+    ASCII_ONLY.store(ascii_only, Ordering::Relaxed);
+
+    #[cfg(windows)]
+    if enabled {
+        enable_ansi_support();
+    }
+}
+
+fn should_colorize(color: ColorChoice, no_color: Option<&str>, is_terminal: bool) -> bool {
+    match color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        // https://no-color.org/ : any non-empty value disables color
+        ColorChoice::Auto => no_color.is_none_or(|v| v.is_empty()) && is_terminal,
+    }
+}
+
+pub fn box_chars() -> &'static BoxChars {
+    if ASCII_ONLY.load(Ordering::Relaxed) {
+        &ASCII_BOX
+    } else {
+        &UNICODE_BOX
+    }
+}
+
+/// Prints the rows in columns under the header, aligned by display width so
+/// that Japanese text lines up. `style` gives the text shown
+/// for a cell (e.g. colored) from its row and column index and plain text,
+/// which is still used for the widths.
+pub fn print_table(
+    header: &[&str],
+    rows: &[Vec<String>],
+    style: impl Fn(usize, usize, &str) -> String,
+) {
+    let mut widths = header.iter().map(|h| h.width()).collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.width());
+        }
+    }
+
+    let box_chars = box_chars();
+    let rule = widths
+        .iter()
+        .map(|w| box_chars.horizontal.repeat(*w))
+        .collect::<Vec<_>>()
+        .join(&format!(
+            "{}{}{}",
+            box_chars.horizontal, box_chars.cross, box_chars.horizontal
+        ));
+
+    let header_cells = header
+        .iter()
+        .zip(&widths)
+        .map(|(h, w)| pad_cell(h, *w))
+        .collect::<Vec<_>>();
+    println!("{}", join_cells(&header_cells));
+    println!("{}", rule);
+
+    for (i, row) in rows.iter().enumerate() {
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, w))| {
+                let padded = pad_cell(cell, *w);
+                let styled = style(i, column, cell);
+                if cell.is_empty() || styled == *cell {
+                    padded
+                } else {
+                    padded.replacen(cell.as_str(), &styled, 1)
+                }
+            })
+            .collect::<Vec<_>>();
+        println!("{}", join_cells(&cells));
+    }
+}
+
+fn pad_cell(cell: &str, width: usize) -> String {
+    let pad = width.saturating_sub(cell.width());
+    format!("{}{}", cell, " ".repeat(pad))
+}
+
+fn join_cells(cells: &[String]) -> String {
+    cells
+        .join(&format!(" {} ", box_chars().vertical))
+        .trim_end()
+        .to_string()
+}
 
 #[cfg(windows)]
-pub fn enable_ansi_support() {
+fn enable_ansi_support() {
     use windows_sys::Win32::System::Console::{
         ENABLE_VIRTUAL_TERMINAL_PROCESSING, GetConsoleMode, GetStdHandle, STD_OUTPUT_HANDLE,
         SetConsoleMode,
@@ -28,3 +160,22 @@ pub fn enable_ansi_support() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_colorize() {
+        assert!(should_colorize(ColorChoice::Auto, None, true));
+        assert!(!should_colorize(ColorChoice::Auto, None, false));
+        assert!(!should_colorize(ColorChoice::Auto, Some("1"), true));
+        assert!(should_colorize(ColorChoice::Auto, Some(""), true));
+    }
+
+    #[test]
+    fn test_should_colorize_with_explicit_choice() {
+        assert!(should_colorize(ColorChoice::Always, Some("1"), false));
+        assert!(!should_colorize(ColorChoice::Never, None, true));
+    }
+}
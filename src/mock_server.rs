@@ -0,0 +1,106 @@
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// How long the server waits for the rest of a request. A client that does
+/// not speak HTTP, such as one starting a TLS handshake, gets its reply then.
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// One response of the mock server.
+pub struct Reply {
+    status: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Reply {
+    pub fn new(status: &'static str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    /// A `200 OK` with `body`.
+    pub fn ok(content_type: &str, body: &str) -> Self {
+        Self::new("200 OK")
+            .header("Content-Type", content_type)
+            .body(body)
+    }
+
+    pub fn header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: &str) -> Self {
+        self.body = body.to_string();
+        self
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {}\r\n", self.status);
+        for (name, value) in &self.headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        ));
+        response.into_bytes()
+    }
+}
+
+/// Answers the requests in order with `replies`, each on its own connection,
+/// and returns what was requested. The base URL is `http://127.0.0.1:<port>`.
+pub fn mock_server(replies: Vec<Reply>) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for reply in replies {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+
+            let request = read_request(&mut stream);
+            requests.push(String::from_utf8_lossy(&request).to_string());
+
+            // the client may have given up already, as in the TLS tests
+            let _ = stream.write_all(&reply.to_bytes());
+        }
+        requests
+    });
+
+    (base, handle)
+}
+
+/// The head and the body of a request, or what came before the client
+/// stopped sending.
+fn read_request(stream: &mut impl Read) -> Vec<u8> {
+    let mut request = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&request[..end]).to_lowercase();
+            let length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if request.len() >= end + 4 + length {
+                return request;
+            }
+        }
+
+        match stream.read(&mut buf) {
+            Ok(0) | Err(_) => return request,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    }
+}
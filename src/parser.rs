@@ -1,26 +1,145 @@
 use crate::{
     error::Error,
-    messages::{INFO_LABEL, SUCCESS_LABEL},
+    i18n::{Msg, tr},
+    layout::InputLayout,
+    literal::{Reader, Value, parse_value},
+    markdown::html_to_markdown,
+    messages::SUCCESS_LABEL,
 };
+use log::{debug, info, warn};
 use regex::Regex;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use crate::file::save_to_file;
 
 #[derive(Debug, PartialEq)]
 pub struct TestCase {
-    input: Vec<String>,
+    input: Vec<Value>,
     output: String,
 }
 
 impl TestCase {
-    fn new(input: Vec<String>, output: String) -> Self {
+    fn new(input: Vec<Value>, output: String) -> Self {
         Self { input, output }
     }
 }
 
-pub fn save_test_cases(test_cases: Vec<TestCase>, problem_id: &str) -> Result<(), Error> {
-    let prefix_path = format!("./p-{}/testcase/", problem_id);
+/// What the problem page tells about the problem besides the test cases.
+#[derive(Debug, Default, PartialEq)]
+pub struct ProblemInfo {
+    pub title: Option<String>,
+    pub function_name: Option<String>,
+    pub arg_count: usize,
+    pub arg_types: Vec<ValueType>,
+    pub return_type: ValueType,
+    /// e.g. `getLowestTemperature(integer high, integer diff) -> integer`,
+    /// with the types the page states, or the guessed ones.
+    pub signature: Option<String>,
+    pub category: Option<String>,
+    pub difficulty: Option<String>,
+    pub time_limit: Option<String>,
+}
+
+/// Type of an argument or a return value, guessed from the samples. It is
+/// saved in `problem.toml` as `int`, `str[]`, `int[][]` and so on.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum ValueType {
+    Int,
+    Float,
+    Bool,
+    Str,
+    Array(Box<ValueType>),
+    #[default]
+    Unknown,
+}
+
+impl ValueType {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => ValueType::Unknown,
+            Value::Bool(_) => ValueType::Bool,
+            Value::Int(_) => ValueType::Int,
+            Value::Float(_) => ValueType::Float,
+            Value::Str(_) => ValueType::Str,
+            Value::Array(items) => ValueType::Array(Box::new(
+                items
+                    .iter()
+                    .map(ValueType::of)
+                    .fold(ValueType::Unknown, ValueType::merge),
+            )),
+        }
+    }
+
+    /// Text that is not a literal, such as an unquoted output, is a string.
+    fn infer(text: &str) -> Self {
+        match parse_value(text) {
+            Ok(value) => ValueType::of(&value),
+            Err(_) if text.trim().is_empty() => ValueType::Unknown,
+            Err(_) => ValueType::Str,
+        }
+    }
+
+    /// The type that fits both samples, e.g. an int and a float give a float.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (ValueType::Unknown, t) | (t, ValueType::Unknown) => t,
+            (ValueType::Int, ValueType::Float) | (ValueType::Float, ValueType::Int) => {
+                ValueType::Float
+            }
+            (ValueType::Array(a), ValueType::Array(b)) => ValueType::Array(Box::new(a.merge(*b))),
+            _ => ValueType::Str,
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueType::Int => f.write_str("int"),
+            ValueType::Float => f.write_str("float"),
+            ValueType::Bool => f.write_str("bool"),
+            ValueType::Str => f.write_str("str"),
+            ValueType::Array(e) => write!(f, "{}[]", e),
+            ValueType::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+impl From<ValueType> for String {
+    fn from(t: ValueType) -> Self {
+        t.to_string()
+    }
+}
+
+impl TryFrom<String> for ValueType {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        if let Some(element) = text.strip_suffix("[]") {
+            return Ok(ValueType::Array(Box::new(element.to_string().try_into()?)));
+        }
+        match text.as_str() {
+            "int" => Ok(ValueType::Int),
+            "float" => Ok(ValueType::Float),
+            "bool" => Ok(ValueType::Bool),
+            "str" => Ok(ValueType::Str),
+            "unknown" => Ok(ValueType::Unknown),
+            _ => Err(format!("unknown type: {}", text)),
+        }
+    }
+}
+
+pub fn save_test_cases(
+    test_cases: Vec<TestCase>,
+    problem_dir: &str,
+    testcase_dir: &str,
+    layout: InputLayout,
+) -> Result<(), Error> {
+    let prefix_path = format!("{}/{}/", problem_dir, testcase_dir);
     let prefix_file = "testcase-";
     let suffix_input_file = ".in";
     let suffix_output_file = ".out";
@@ -32,11 +151,15 @@ pub fn save_test_cases(test_cases: Vec<TestCase>, problem_id: &str) -> Result<()
             "{}{}{}{}",
             prefix_path, prefix_file, index, suffix_input_file
         );
-        let input_flle_content = format_vec_str(&case.input);
-        println!("[{}] testcase {}", *INFO_LABEL, index);
+        let input_flle_content = layout.format(&case.input);
+        info!("testcase {}", index);
 
         save_to_file(&input_file_path, &input_flle_content)?;
-        println!("[{}] Saved to : {}", *SUCCESS_LABEL, &input_file_path);
+        println!(
+            "[{}] {}",
+            *SUCCESS_LABEL,
+            tr!(Msg::SavedTo, input_file_path)
+        );
 
         // save output file
         let output_file_path = format!(
@@ -44,8 +167,12 @@ pub fn save_test_cases(test_cases: Vec<TestCase>, problem_id: &str) -> Result<()
             prefix_path, prefix_file, index, suffix_output_file
         );
         let output_file_contnet = &case.output;
-        save_to_file(&output_file_path, &output_file_contnet)?;
-        println!("[{}] Saved to : {}", *SUCCESS_LABEL, &output_file_path);
+        save_to_file(&output_file_path, output_file_contnet)?;
+        println!(
+            "[{}] {}",
+            *SUCCESS_LABEL,
+            tr!(Msg::SavedTo, output_file_path)
+        );
         println!();
     }
 
@@ -53,16 +180,13 @@ pub fn save_test_cases(test_cases: Vec<TestCase>, problem_id: &str) -> Result<()
 }
 
 pub fn get_test_cases(html: &str) -> Result<Vec<TestCase>, Error> {
-    let raw_test_cases = pickup_test_case(html)?;
+    let raw_test_cases = pickup_raw_cases(html)?;
 
     let result: Result<Vec<TestCase>, Error> = raw_test_cases
         .into_iter()
-        .map(|line| {
-            let inputs = parse_input_case(&line)?
-                .split(",")
-                .map(|s| s.trim().to_string())
-                .collect::<Vec<_>>();
-            let output = parse_output_case(&line)?;
+        .map(|case| {
+            let inputs = resolve_inputs(&case)?;
+            let output = resolve_output(&case)?;
             Ok(TestCase::new(inputs, output))
         })
         .collect();
@@ -70,85 +194,277 @@ pub fn get_test_cases(html: &str) -> Result<Vec<TestCase>, Error> {
     result
 }
 
-fn format_vec_str(s: &Vec<String>) -> String {
-    s.iter()
-        .map(|s| s.as_str())
-        .collect::<Vec<&str>>()
+/// Takes the arguments from the `data-case` JSON, checking them against the
+/// visible text. The text is only used when the JSON cannot be read.
+fn resolve_inputs(case: &RawCase) -> Result<Vec<Value>, Error> {
+    let from_text = parse_input_case(&case.text);
+
+    let Some(from_data) = parse_data_case(&case.data_case) else {
+        warn!(
+            "data-case {:?} is not a JSON array of arguments, the text is used instead: {}",
+            case.data_case, case.text
+        );
+        return from_text;
+    };
+
+    match from_text {
+        Ok(from_text) if from_text != from_data => warn!(
+            "data-case {:?} does not match the text, data-case is used: {}",
+            case.data_case, case.text
+        ),
+        Err(e) => warn!("the text could not be parsed, data-case is used: {}", e),
+        Ok(_) => {}
+    }
+
+    Ok(from_data)
+}
+
+/// Takes the output from the text. When the arguments there cannot be parsed
+/// but `data-case` gives them, the output is what follows the last `-->`.
+fn resolve_output(case: &RawCase) -> Result<String, Error> {
+    match parse_output_case(&case.text) {
+        Err(_) if parse_data_case(&case.data_case).is_some() => output_after_last_arrow(&case.text),
+        result => result,
+    }
+}
+
+fn output_after_last_arrow(test_case: &str) -> Result<String, Error> {
+    let output = test_case
+        .rsplit_once("-->")
+        .map(|(_, output)| output.trim())
+        .unwrap_or_default();
+    if output.is_empty() {
+        return Err(Error::Literal(format!("no output: {}", test_case)));
+    }
+    Ok(output.to_string())
+}
+
+fn parse_data_case(data_case: &str) -> Option<Vec<Value>> {
+    match serde_json::from_str::<serde_json::Value>(data_case).ok()? {
+        serde_json::Value::Array(items) => items.iter().map(Value::from_json).collect(),
+        _ => None,
+    }
+}
+
+pub const CATEGORY_LABELS: &[&str] = &["カテゴリー", "カテゴリ", "Category"];
+pub const DIFFICULTY_LABELS: &[&str] = &["難易度", "Difficulty"];
+
+pub fn get_problem_info(html: &str) -> Result<ProblemInfo, Error> {
+    let function_name = match pickup_test_case(html)?.first() {
+        Some(case) => parse_function_name(case)?,
+        None => None,
+    };
+
+    let test_cases = get_test_cases(html)?;
+    let arg_count = test_cases.first().map_or(0, |case| case.input.len());
+
+    let mut arg_types = vec![ValueType::Unknown; arg_count];
+    let mut return_type = ValueType::Unknown;
+    for case in &test_cases {
+        for (arg_type, input) in arg_types.iter_mut().zip(&case.input) {
+            *arg_type = std::mem::take(arg_type).merge(ValueType::of(input));
+        }
+        return_type = return_type.merge(ValueType::infer(&case.output));
+    }
+
+    let document = Html::parse_document(html);
+    let signature = function_name.as_ref().map(|name| {
+        let params =
+            match pickup_labeled(document.root_element(), &["入力のデータ型", "Input type"])
+            {
+                Some(params) => params,
+                None => arg_types
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+        let result = pickup_labeled(document.root_element(), &["出力のデータ型", "Output type"])
+            .unwrap_or(return_type.to_string());
+        format!("{}({}) -> {}", name, params, result)
+    });
+
+    Ok(ProblemInfo {
+        title: pickup_title(html)?,
+        function_name,
+        arg_count,
+        arg_types,
+        return_type,
+        signature,
+        category: pickup_labeled(document.root_element(), CATEGORY_LABELS),
+        difficulty: pickup_labeled(document.root_element(), DIFFICULTY_LABELS),
+        time_limit: pickup_labeled(
+            document.root_element(),
+            &["実行時間制限", "制限時間", "Time limit"],
+        ),
+    })
+}
+
+/// The value shown next to a label, as in `<dt>難易度</dt><dd>初級</dd>` or
+/// `<p>難易度： 初級</p>`. The innermost element starting with the label wins.
+pub fn pickup_labeled(root: ElementRef, labels: &[&str]) -> Option<String> {
+    let mut best: Option<(usize, String)> = None;
+
+    for element in root.descendants().filter_map(ElementRef::wrap) {
+        let text = collapse_text(element);
+        let Some(rest) = labels.iter().find_map(|label| text.strip_prefix(label)) else {
+            continue;
+        };
+
+        let rest = rest.trim_start_matches([':', '：', ' ']).trim();
+        let value = if rest.is_empty() {
+            match element.next_siblings().find_map(ElementRef::wrap) {
+                Some(sibling) => collapse_text(sibling),
+                None => continue,
+            }
+        } else {
+            rest.to_string()
+        };
+
+        if !value.is_empty() && best.as_ref().is_none_or(|(len, _)| text.len() < *len) {
+            best = Some((text.len(), value));
+        }
+    }
+
+    best.map(|(_, value)| value)
+}
+
+pub fn collapse_text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
         .join(" ")
 }
 
+/// The problem statement as Markdown, taken from the element holding it, or
+/// the whole body when the page has no such element.
+pub fn get_statement(html: &str) -> Result<Option<String>, Error> {
+    let document = Html::parse_document(html);
+
+    for selector in [
+        ".problem-statement",
+        "#problem-statement",
+        ".problem-body",
+        "article",
+        "main",
+        "body",
+    ] {
+        let selector = Selector::parse(selector).map_err(selector_error)?;
+        if let Some(element) = document.select(&selector).next() {
+            debug!("statement taken from {:?}", element.value().name());
+            return Ok(Some(html_to_markdown(element)));
+        }
+    }
+
+    Ok(None)
+}
+
+fn pickup_title(html: &str) -> Result<Option<String>, Error> {
+    let document = Html::parse_document(html);
+
+    for selector in ["h1", "title"] {
+        let selector = Selector::parse(selector).map_err(selector_error)?;
+        let title = document
+            .select(&selector)
+            .map(|elem| elem.text().collect::<String>().trim().to_string())
+            .find(|text| !text.is_empty());
+        if title.is_some() {
+            return Ok(title);
+        }
+    }
+
+    Ok(None)
+}
+
+fn parse_function_name(test_case: &str) -> Result<Option<String>, Error> {
+    let re = Regex::new(r"^\s*([A-Za-z_][A-Za-z0-9_]*)\s*\(")
+        .map_err(|_| Error::Internal("Regex compile error in parse_function_name".to_string()))?;
+
+    Ok(re.captures(test_case).map(|cap| cap[1].to_string()))
+}
+
 fn selector_error<E: std::fmt::Display>(e: E) -> Error {
     Error::Selector(e.to_string().into())
 }
 
 fn pickup_test_case(html: &str) -> Result<Vec<String>, Error> {
+    Ok(pickup_raw_cases(html)?
+        .into_iter()
+        .map(|case| case.text)
+        .collect())
+}
+
+/// A sample as found in the page: the visible text like
+/// `getLowestTemperature(3,2) --> 1` and the `data-case` attribute like `[3,2]`.
+struct RawCase {
+    text: String,
+    data_case: String,
+}
+
+fn pickup_raw_cases(html: &str) -> Result<Vec<RawCase>, Error> {
     let document = Html::parse_document(html);
 
     let p_selector = Selector::parse("p").map_err(selector_error)?;
     let span_selector = Selector::parse("span[data-case]").map_err(selector_error)?;
 
-    let mut targets = Vec::<String>::new();
+    let mut targets = Vec::<RawCase>::new();
 
     for p_elem in document.select(&p_selector) {
-        let has_target_span = p_elem
+        let data_case = p_elem
             .select(&span_selector)
-            .any(|span| span.value().attr("data-case").is_some());
-
-        if has_target_span {
+            .find_map(|span| span.value().attr("data-case"));
+        if let Some(data_case) = data_case {
             let text = p_elem
                 .text()
                 .collect::<Vec<_>>()
                 .join("")
                 .trim()
                 .to_string();
-            targets.push(text);
+            debug!("matched p > span[data-case={:?}]: {:?}", data_case, text);
+            targets.push(RawCase {
+                text,
+                data_case: data_case.to_string(),
+            });
         }
     }
+
+    debug!("{} test cases found in the page", targets.len());
     Ok(targets)
 }
 
-fn parse_input_case(test_case: &str) -> Result<String, Error> {
-    let re = Regex::new(r"\(([^)]+)\)")
-        .map_err(|_| Error::Internal("Regex compile error in parse_input_case".to_string()))?;
+/// Reads the arguments of `name(args) --> output`, leaving the reader right
+/// after the closing parenthesis.
+fn read_arguments(test_case: &str) -> Result<(Vec<Value>, Reader), Error> {
+    let open = test_case
+        .find('(')
+        .ok_or(Error::Literal(format!("no argument list: {}", test_case)))?;
 
-    let cap = re.captures(&test_case).ok_or(Error::Internal(
-        "Regex capture error in parse_input_case".to_string(),
-    ))?;
-    let inside = &cap[1];
-    let result = inside
-        .split(",")
-        .map(|s| s.trim())
-        .collect::<Vec<_>>()
-        .join(",")
-        .to_string();
-    Ok(result)
+    let mut reader = Reader::new(&test_case[open + 1..]);
+    let args = reader.list(Some(')'))?;
+    Ok((args, reader))
+}
+
+fn parse_input_case(test_case: &str) -> Result<Vec<Value>, Error> {
+    Ok(read_arguments(test_case)?.0)
 }
 
 fn parse_output_case(test_case: &str) -> Result<String, Error> {
-    let re = Regex::new(r"--> (.+)$")
-        .map_err(|_| Error::Internal("Regex compili error in parse_output_case".to_string()))?;
+    let (_, mut reader) = read_arguments(test_case)?;
+    reader.expect("-->")?;
 
-    let cap = re.captures(&test_case).ok_or(Error::Internal(
-        "Regex capture error in parse_output_case".to_string(),
-    ))?;
-    let result = cap[1].to_string();
-    Ok(result)
+    let output = reader.rest().trim().to_string();
+    if output.is_empty() {
+        return Err(Error::Literal(format!("no output: {}", test_case)));
+    }
+    Ok(output)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_format_vec_str() {
-        let v = vec!["1".to_string(), "2".to_string(), "3".to_string()];
-
-        let actual = format_vec_str(&v);
-        let expected = "1 2 3".to_string();
-
-        assert_eq!(actual, expected);
-    }
-
     #[test]
     fn test_get_test_cases() {
         let html = r#"
@@ -177,14 +493,90 @@ mod tests {
         let results = get_test_cases(html).unwrap();
 
         let expecteds = vec![
-            TestCase::new(vec!["3".to_string(), "2".to_string()], "1".to_string()),
-            TestCase::new(vec!["2".to_string(), "10".to_string()], "-8".to_string()),
-            TestCase::new(vec!["18".to_string(), "5".to_string()], "13".to_string()),
+            TestCase::new(vec![Value::Int(3), Value::Int(2)], "1".to_string()),
+            TestCase::new(vec![Value::Int(2), Value::Int(10)], "-8".to_string()),
+            TestCase::new(vec![Value::Int(18), Value::Int(5)], "13".to_string()),
         ];
 
         assert_eq!(results, expecteds);
     }
 
+    #[test]
+    fn test_get_test_cases_prefers_data_case() {
+        let html = r#"
+        <p>
+            join("a, b", 2) --> 1
+            <span data-case='["a, b",3]'></span>
+        </p>
+        <p>
+            join("c", 4) --> 2
+            <span data-case="not json"></span>
+        </p>
+        "#;
+
+        let results = get_test_cases(html).unwrap();
+
+        let expecteds = vec![
+            TestCase::new(
+                vec![Value::Str("a, b".to_string()), Value::Int(3)],
+                "1".to_string(),
+            ),
+            TestCase::new(
+                vec![Value::Str("c".to_string()), Value::Int(4)],
+                "2".to_string(),
+            ),
+        ];
+
+        assert_eq!(results, expecteds);
+    }
+
+    #[test]
+    fn test_get_test_cases_with_unparseable_text_uses_data_case() {
+        let html = r#"
+        <p>
+            getX(3 2) --> 1
+            <span data-case="[3,2]"></span>
+        </p>
+        <p>
+            getX(3 2) --> 1
+            <span data-case="not json"></span>
+        </p>
+        "#;
+
+        let first = r#"<p>getX(3 2) --> 1<span data-case="[3,2]"></span></p>"#;
+        assert_eq!(
+            get_test_cases(first).unwrap(),
+            vec![TestCase::new(
+                vec![Value::Int(3), Value::Int(2)],
+                "1".to_string()
+            )]
+        );
+        assert!(get_test_cases(html).is_err());
+    }
+
+    #[test]
+    fn test_output_after_last_arrow() {
+        assert_eq!(output_after_last_arrow("f(--> 2) --> 1").unwrap(), "1");
+        assert!(output_after_last_arrow("f(1) -->").is_err());
+        assert!(output_after_last_arrow("f(1)").is_err());
+    }
+
+    #[test]
+    fn test_parse_data_case() {
+        assert_eq!(
+            parse_data_case(r#"[[1,2],"x",true,null,1.5]"#),
+            Some(vec![
+                Value::Array(vec![Value::Int(1), Value::Int(2)]),
+                Value::Str("x".to_string()),
+                Value::Bool(true),
+                Value::Null,
+                Value::Float(1.5),
+            ])
+        );
+        assert_eq!(parse_data_case("3"), None);
+        assert_eq!(parse_data_case(r#"[{"a":1}]"#), None);
+    }
+
     #[test]
     fn test_paser_output_case() {
         let case = "getLowestTemperature(3,2) --> 1";
@@ -201,11 +593,142 @@ mod tests {
 
         let actual = parse_input_case(case).unwrap();
 
-        let expected = "3,2";
+        let expected = [Value::Int(3), Value::Int(2)];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_input_case_with_literals() {
+        let case = r#"split("a,b)", [[1,2],[3,4]], true) --> ["a","b)"]"#;
+
+        let actual = parse_input_case(case).unwrap();
+
+        let expected = [
+            Value::Str("a,b)".to_string()),
+            Value::Array(vec![
+                Value::Array(vec![Value::Int(1), Value::Int(2)]),
+                Value::Array(vec![Value::Int(3), Value::Int(4)]),
+            ]),
+            Value::Bool(true),
+        ];
+
+        assert_eq!(actual, expected);
+        assert_eq!(parse_output_case(case).unwrap(), r#"["a","b)"]"#);
+    }
+
+    #[test]
+    fn test_parse_case_without_arguments() {
+        let case = "getAnswer() --> 42";
+
+        assert_eq!(parse_input_case(case).unwrap(), []);
+        assert_eq!(parse_output_case(case).unwrap(), "42");
+    }
+
+    #[test]
+    fn test_parse_output_case_with_arrow_in_argument() {
+        let case = r#"arrow("--> x") --> 1"#;
+
+        assert_eq!(parse_output_case(case).unwrap(), "1");
+    }
+
+    #[test]
+    fn test_get_problem_info() {
+        let html = r#"
+        <h1> 最低気温 </h1>
+        <p class="m-0 rem0p8">
+            getLowestTemperature(3,2) --> 1
+            <span class="cursor-pointer judge-test-button ml-1" data-case="[3,2]">
+                <i class="far fa-play-circle" title="テスト実行"></i>
+            </span>
+        </p>
+        "#;
+
+        let actual = get_problem_info(html).unwrap();
+
+        let expected = ProblemInfo {
+            title: Some("最低気温".to_string()),
+            function_name: Some("getLowestTemperature".to_string()),
+            arg_count: 2,
+            arg_types: vec![ValueType::Int, ValueType::Int],
+            return_type: ValueType::Int,
+            signature: Some("getLowestTemperature(int, int) -> int".to_string()),
+            ..Default::default()
+        };
 
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_get_problem_info_with_labels() {
+        let html = r#"
+        <h1>最低気温</h1>
+        <dl><dt>難易度</dt><dd>初級</dd><dt>カテゴリー</dt><dd>数学</dd></dl>
+        <p>実行時間制限： 1秒</p>
+        <p>入力のデータ型： <code>integer high, integer diff</code></p>
+        <p>出力のデータ型： <code>integer</code></p>
+        <p>
+            getLowestTemperature(3,2) --> 1
+            <span data-case="[3,2]"></span>
+        </p>
+        "#;
+
+        let actual = get_problem_info(html).unwrap();
+
+        assert_eq!(actual.difficulty.as_deref(), Some("初級"));
+        assert_eq!(actual.category.as_deref(), Some("数学"));
+        assert_eq!(actual.time_limit.as_deref(), Some("1秒"));
+        assert_eq!(
+            actual.signature.as_deref(),
+            Some("getLowestTemperature(integer high, integer diff) -> integer")
+        );
+    }
+
+    #[test]
+    fn test_get_statement() {
+        let html = r#"
+        <nav>menu</nav>
+        <div class="problem-statement"><h4>制約</h4><ul><li>1 &lt;= n</li></ul></div>
+        "#;
+
+        assert_eq!(
+            get_statement(html).unwrap().as_deref(),
+            Some("#### 制約\n\n- 1 <= n\n")
+        );
+    }
+
+    #[test]
+    fn test_infer_value_type() {
+        assert_eq!(ValueType::infer("-8"), ValueType::Int);
+        assert_eq!(ValueType::infer("1.5"), ValueType::Float);
+        assert_eq!(ValueType::infer("false"), ValueType::Bool);
+        assert_eq!(ValueType::infer("\"abc\""), ValueType::Str);
+        assert_eq!(
+            ValueType::infer("[[1,2],[3]]"),
+            ValueType::Array(Box::new(ValueType::Array(Box::new(ValueType::Int))))
+        );
+        assert_eq!(
+            ValueType::infer("[]"),
+            ValueType::Array(Box::new(ValueType::Unknown))
+        );
+    }
+
+    #[test]
+    fn test_value_type_text() {
+        let t = ValueType::Array(Box::new(ValueType::Array(Box::new(ValueType::Str))));
+
+        assert_eq!(t.to_string(), "str[][]");
+        assert_eq!(ValueType::try_from("str[][]".to_string()), Ok(t));
+        assert!(ValueType::try_from("map".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_merge_value_type() {
+        assert_eq!(ValueType::Int.merge(ValueType::Float), ValueType::Float);
+        assert_eq!(ValueType::Unknown.merge(ValueType::Bool), ValueType::Bool);
+        assert_eq!(ValueType::Int.merge(ValueType::Bool), ValueType::Str);
+    }
+
     #[test]
     fn test_pickup_test_case() {
         let html = r#"
@@ -231,7 +754,7 @@ mod tests {
         </p>
         "#;
 
-        let expecteds = vec![
+        let expecteds = [
             "getLowestTemperature(3,2) --> 1",
             "getLowestTemperature(2,10) --> -8",
             "getLowestTemperature(18,5) --> 13",
@@ -0,0 +1,123 @@
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::{
+    error::Error,
+    file::save_to_file,
+    layout::InputLayout,
+    parser::{ProblemInfo, ValueType},
+};
+
+pub const PROBLEM_FILE_NAME: &str = "problem.toml";
+/// The problem statement converted to Markdown.
+pub const STATEMENT_FILE_NAME: &str = "README.md";
+
+/// Metadata written to `problem.toml` in each problem directory, so that
+/// commands run later (e.g. `judge --function`) know about the problem.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Problem {
+    pub id: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function_name: Option<String>,
+    pub arg_count: usize,
+    pub arg_types: Vec<ValueType>,
+    /// How the arguments are written in the `.in` files.
+    pub input_layout: InputLayout,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<String>,
+    /// As the page shows it, e.g. `1秒`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_limit: Option<String>,
+    /// When the problem was downloaded, in RFC 3339.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloaded_at: Option<String>,
+}
+
+impl Problem {
+    pub fn new(id: &str, url: &str, info: &ProblemInfo, input_layout: InputLayout) -> Self {
+        Self {
+            id: id.to_string(),
+            url: url.to_string(),
+            title: info.title.clone(),
+            function_name: info.function_name.clone(),
+            arg_count: info.arg_count,
+            arg_types: info.arg_types.clone(),
+            input_layout,
+            signature: info.signature.clone(),
+            category: info.category.clone(),
+            difficulty: info.difficulty.clone(),
+            time_limit: info.time_limit.clone(),
+            downloaded_at: Some(
+                chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            ),
+        }
+    }
+
+    /// Reads `problem.toml` in `dir`, if the directory has one.
+    pub fn load(dir: &Path) -> Result<Option<Self>, Error> {
+        let path = dir.join(PROBLEM_FILE_NAME);
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        debug!("load problem: {:?}", path);
+        let contents = fs::read_to_string(&path)?;
+        toml::from_str(&contents)
+            .map(Some)
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, dir: &str) -> Result<(), Error> {
+        let contents = toml::to_string(self).map_err(|e| Error::Internal(e.to_string()))?;
+        save_to_file(&Path::new(dir).join(PROBLEM_FILE_NAME), &contents)?;
+        Ok(())
+    }
+
+    /// Writes the statement to `README.md`, headed by the title and the URL.
+    pub fn save_statement(&self, dir: &str, statement: &str) -> Result<(), Error> {
+        let title = self.title.as_deref().unwrap_or(&self.id);
+        let contents = format!("# {}\n\n<{}>\n\n{}", title, self.url, statement);
+        save_to_file(&Path::new(dir).join(STATEMENT_FILE_NAME), &contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem_round_trip() {
+        let info = ProblemInfo {
+            title: Some("最低気温".to_string()),
+            function_name: Some("getLowestTemperature".to_string()),
+            arg_count: 2,
+            arg_types: vec![ValueType::Array(Box::new(ValueType::Int)), ValueType::Str],
+            ..Default::default()
+        };
+        let problem = Problem::new(
+            "1",
+            "https://recursionist.io/dashboard/problems/1",
+            &info,
+            InputLayout::Counted,
+        );
+
+        let contents = toml::to_string(&problem).unwrap();
+        let loaded: Problem = toml::from_str(&contents).unwrap();
+
+        assert_eq!(loaded, problem);
+        assert!(contents.contains("function_name = \"getLowestTemperature\""));
+        assert!(contents.contains("arg_types = [\"int[]\", \"str\"]"));
+        assert!(contents.contains("input_layout = \"counted\""));
+        assert!(contents.contains("downloaded_at = "));
+    }
+}
@@ -1,70 +1,93 @@
 use std::{
-    collections::HashMap,
-    fs::{self, File},
+    collections::{HashMap, VecDeque},
+    fs::File,
     io::{BufRead, BufReader},
     path::Path,
-    sync::Arc,
+    sync::{Arc, Mutex, mpsc},
+    thread,
+    time::{Duration, Instant},
 };
 
+use colored::Colorize;
+use log::{debug, info, warn};
 use regex::Regex;
 use reqwest::{
-    Url,
-    blocking::{Client, Response},
+    StatusCode, Url,
+    blocking::{Client, RequestBuilder, Response},
     cookie::{CookieStore, Jar},
-    header::LOCATION,
+    header::{ACCEPT, HeaderMap, LOCATION, RETRY_AFTER},
 };
 use scraper::{Html, Selector};
 
 use crate::{
+    cache::{CacheMode, PageCache},
+    catalog::next_page_url,
+    config::Config,
     error::Error,
-    file::{get_cookie_path, save_to_file},
-    messages::{FAILED_LABEL, INFO_LABEL, NETWORK_LABEL, SUCCESS_LABEL},
-    parser::{get_test_cases, save_test_cases},
+    file::{get_cookie_path, read_file, save_to_file},
+    i18n::Msg,
+    i18n::tr,
+    layout::InputLayout,
+    logger::{NETWORK, redact_header},
+    messages::{FAILED_LABEL, INFO_LABEL, SUCCESS_LABEL, print_table},
+    network::{NetworkSettings, backoff, configure_client, is_retryable_status, retry_after},
+    parser::{get_problem_info, get_statement, get_test_cases, save_test_cases},
+    problem::Problem,
+    site::Site,
+    stub::{StubLanguage, write_stub},
+    template::{Template, copy_template, resolve_template, template_vars},
 };
 
 type Cookie = HashMap<String, String>;
+#[allow(clippy::upper_case_acronyms)]
 type HTML = String;
 
+/// Upper bound of `download.jobs`, to stay polite to the site.
+const MAX_JOBS: usize = 4;
+/// Pages of the problem index followed at most, in case the links loop.
+const MAX_INDEX_PAGES: usize = 100;
+
+#[allow(clippy::upper_case_acronyms)]
 enum Redirect {
     ON,
     OFF,
 }
 
-pub fn initial_auth(email: &str, password: &str) -> Result<(), Error> {
-    println!("[{}] Start Login process.", *INFO_LABEL);
+pub fn initial_auth(
+    email: &str,
+    password: &str,
+    site: &Site,
+    network: &NetworkSettings,
+) -> Result<(), Error> {
+    info!("Start Login process.");
 
     let jar = Arc::new(Jar::default());
-    let client_get = create_client(Redirect::ON, &jar)?;
-
-    let request_path = "https://recursionist.io/login";
-    println!("[{}] GET: {}", *NETWORK_LABEL, request_path);
+    let client_get = create_client(Redirect::ON, &jar, network)?;
 
-    let res = client_get.get(request_path).send()?;
-    println!("[{}] {}", *NETWORK_LABEL, res.status());
+    let request_path = site.login_url();
+    let res = send(client_get.get(&request_path))?;
 
     let login_html = res.text()?;
 
     let token = extract_token_from_html(&login_html)?;
 
-    let client_post = create_client(Redirect::OFF, &jar)?;
+    let client_post = create_client(Redirect::OFF, &jar, network)?;
 
     let mut form = HashMap::new();
     form.insert("email", email);
     form.insert("password", password);
     form.insert("_token", &token);
 
-    println!("[{}] Send Login request...", *INFO_LABEL);
-    println!("[{}] POST: {}", *NETWORK_LABEL, request_path);
-
-    let res = client_post
-        .post(request_path)
-        .form(&form)
-        .header("Content-Type", "application/x-www-form-urlencoded")
-        .header("Referer", "https://recursionist.io/")
-        .header("Origin", "https://recursionist.io")
-        .send()?;
+    info!("Send Login request...");
 
-    println!("[{}] {}", *NETWORK_LABEL, res.status());
+    let res = send(
+        client_post
+            .post(&request_path)
+            .form(&form)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Referer", format!("{}/", site.base_url()))
+            .header("Origin", site.base_url()),
+    )?;
 
     let location = res
         .headers()
@@ -77,9 +100,9 @@ pub fn initial_auth(email: &str, password: &str) -> Result<(), Error> {
             Error::Internal("Failed to parse Location header as UTF-8 in initial_auth".to_string())
         })?;
 
-    if is_login_successful(location) {
-        println!("[{}] Login success.", *SUCCESS_LABEL);
-        let url = Url::parse("https://recursionist.io").map_err(|_| Error::UrlIncorrectFormat)?;
+    if is_login_successful(site, location) {
+        println!("[{}] {}", *SUCCESS_LABEL, Msg::LoginSuccess);
+        let url = Url::parse(site.base_url()).map_err(|_| Error::UrlIncorrectFormat)?;
         let cookies = jar.cookies(&url).ok_or(Error::CookieMissing)?;
         let cookie_str = cookies
             .to_str()
@@ -89,154 +112,671 @@ pub fn initial_auth(email: &str, password: &str) -> Result<(), Error> {
         save_cookie_to_file(cookie_str)?;
         Ok(())
     } else {
-        println!("[{}] Login failed.", *FAILED_LABEL);
+        println!("[{}] {}", *FAILED_LABEL, Msg::LoginFailure);
         Err(Error::LoginFailed)
     }
 }
 
-pub fn download(arg_s: &str) -> Result<(), Error> {
-    let url = if is_natural_number(arg_s) {
-        create_url(arg_s)
-    } else {
-        arg_s.to_string()
-    };
+pub struct DownloadOptions {
+    /// Template name or directory given with `--template`.
+    pub template: Option<String>,
+    /// Language to generate a stub for.
+    pub stub: Option<String>,
+    /// Layout of the `.in` files, overriding `download.layout`.
+    pub layout: Option<InputLayout>,
+    /// Pages fetched at the same time, overriding `download.jobs`.
+    pub jobs: Option<usize>,
+    /// How the cached problem pages are used.
+    pub cache: CacheMode,
+}
 
-    if !valid_problem_url(&url)? {
-        return Err(Error::UrlIncorrectFormat);
+/// `DownloadOptions` checked against the config, before anything is fetched.
+struct Setup {
+    template: Option<Template>,
+    stub: Option<StubLanguage>,
+    layout: InputLayout,
+}
+
+impl Setup {
+    fn resolve(options: &DownloadOptions, config: &Config) -> Result<Self, Error> {
+        Ok(Self {
+            template: resolve_template(options.template.as_deref(), config)?,
+            stub: options
+                .stub
+                .as_deref()
+                .map(StubLanguage::from_name)
+                .transpose()?,
+            layout: options
+                .layout
+                .or(config.download.layout)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Downloads every problem in `targets` and in the lines of `from_file`. One
+/// problem is downloaded as is; several share one session, skip the problems
+/// already downloaded and end with a table of the results.
+pub fn download(
+    targets: &[String],
+    from_file: Option<&str>,
+    options: &DownloadOptions,
+    config: &Config,
+) -> Result<(), Error> {
+    let mut targets = targets.to_vec();
+    if let Some(file) = from_file {
+        targets.extend(read_targets(&read_file(file)?));
     }
+    let site = config.site()?;
+    let urls = parse_targets(&site, &targets)?;
 
-    let html = fetch_problem_page(&url)?;
+    let setup = Setup::resolve(options, config)?;
 
-    let problem_id = extract_url_number(&url)?;
+    let session = Session::load_cached(options.cache, &config.network())?;
 
-    let test_cases = get_test_cases(&html)?;
-    save_test_cases(test_cases, &problem_id)?;
+    match urls.as_slice() {
+        [url] => {
+            let html = session.fetch(url)?;
+            session.save_cookie(url)?;
 
-    let path = format!("./p-{}", problem_id);
-    move_files_template(&path)?;
+            let problem_id = site.problem_id(url).ok_or(Error::UrlIncorrectFormat)?;
 
-    Ok(())
+            save_problem(&html, &problem_id, url, &setup, config)
+        }
+        _ => {
+            let ids = urls
+                .iter()
+                .map(|url| site.problem_id(url).ok_or(Error::UrlIncorrectFormat))
+                .collect::<Result<Vec<_>, _>>()?;
+            let jobs = options.jobs.unwrap_or(config.download_jobs());
+            download_all(&ids, &urls, &session, jobs, &setup, config)
+        }
+    }
 }
 
-fn move_files_template(dest_dir: &str) -> Result<(), Error> {
-    let dir_name = "template";
-    let template_path = Path::new(dir_name);
-    let dest_path = Path::new(dest_dir);
+/// Does what `download` does with a problem page saved as `file`, without
+/// going online. The problem number is `id`, or the one the page links to.
+pub fn import(
+    file: &str,
+    id: Option<u32>,
+    options: &DownloadOptions,
+    config: &Config,
+) -> Result<(), Error> {
+    let setup = Setup::resolve(options, config)?;
+
+    let html = read_file(file)?;
+
+    let problem_id = match id {
+        Some(id) => id.to_string(),
+        None => find_problem_id(&html, &config.site()?)?.ok_or(Error::NoProblemId)?,
+    };
+    let url = create_url(&config.site()?, &problem_id);
+    info!("import {} as problem {}", file, problem_id);
+
+    save_problem(&html, &problem_id, &url, &setup, config)
+}
 
-    if template_path.is_dir() && dest_path.exists() {
-        copy_all_files_recursive(template_path, dest_path)?;
+fn save_problem(
+    html: &str,
+    problem_id: &str,
+    url: &str,
+    setup: &Setup,
+    config: &Config,
+) -> Result<(), Error> {
+    let test_cases = get_test_cases(html)?;
+    let path = config.problem_dir(problem_id);
+    save_test_cases(test_cases, &path, config.testcase_dir(), setup.layout)?;
+
+    let info = get_problem_info(html)?;
+    let problem = Problem::new(problem_id, url, &info, setup.layout);
+    problem.save(&path)?;
+    if let Some(statement) = get_statement(html)? {
+        problem.save_statement(&path, &statement)?;
+    }
+    let vars = template_vars(problem_id, url, &info);
+    if let Some(template) = &setup.template {
+        copy_template(template, &path, &vars)?;
+    }
+    if let Some(language) = setup.stub {
+        write_stub(language, &info, setup.layout, &path, config)?;
     }
 
     Ok(())
 }
 
-fn copy_all_files_recursive(src: &Path, dest: &Path) -> Result<(), Error> {
-    if !dest.exists() {
-        fs::create_dir_all(dest)
-            .map_err(|_| Error::Internal("Failed to create directory".to_string()))?;
+/// The number of the problem a saved page was saved from: the URL browsers
+/// record in a `saved from url` comment, or the canonical URL of the page,
+/// matched against the problem path of `site`.
+fn find_problem_id(html: &str, site: &Site) -> Result<Option<String>, Error> {
+    let saved_from = Regex::new(r"saved from url=\(\d+\)(\S+)")
+        .map_err(|_| Error::Internal("Regex compile error in find_problem_id".to_string()))?;
+    let mut urls = saved_from
+        .captures(html)
+        .map(|cap| cap[1].to_string())
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let document = Html::parse_document(html);
+    for (selector, attr) in [
+        ("link[rel=canonical]", "href"),
+        ("meta[property='og:url']", "content"),
+    ] {
+        let selector =
+            Selector::parse(selector).map_err(|e| Error::Selector(e.to_string().into()))?;
+        urls.extend(
+            document
+                .select(&selector)
+                .filter_map(|elem| elem.value().attr(attr))
+                .map(|url| url.to_string()),
+        );
     }
 
-    for entry in
-        fs::read_dir(src).map_err(|_| Error::Internal("Failed to read directory".to_string()))?
-    {
-        let entry =
-            entry.map_err(|_| Error::Internal("Failed to get directory entry".to_string()))?;
-        let path = entry.path();
-        let relative_path = path
-            .strip_prefix(src)
-            .map_err(|_| Error::Internal("Failed to get relative path".to_string()))?;
-        let new_path = dest.join(relative_path);
-
-        if path.is_dir() {
-            copy_all_files_recursive(&path, &new_path)?;
-        } else if path.is_file() {
-            if let Some(parent) = new_path.parent() {
-                fs::create_dir_all(parent)
-                    .map_err(|_| Error::Internal("Failed to create paret direcotry".to_string()))?;
-            }
-            fs::copy(&path, &new_path)
-                .map_err(|_| Error::Internal("Failed to copy file".to_string()))?;
+    debug!("urls found in the page: {:?}", urls);
+    let problem_link = site.problem_link()?;
+    Ok(urls.iter().find_map(|url| {
+        problem_link
+            .captures(url.trim())
+            .map(|caps| caps[1].to_string())
+    }))
+}
+
+/// Fetches every page of the dashboard problem index, following the links
+/// to the next page.
+pub fn fetch_problem_index(config: &Config) -> Result<Vec<HTML>, Error> {
+    let session = Session::load(&config.network())?;
+    let rate_limit = RateLimit::new(config.download_interval());
+
+    let mut url = config.site()?.problem_index_url();
+    let mut visited = Vec::new();
+    let mut pages = Vec::new();
+    loop {
+        rate_limit.wait();
+        let html = session.fetch(&url)?;
+        let next = next_page_url(&html, &url)?;
+        visited.push(url.clone());
+        pages.push(html);
+
+        match next {
+            Some(next) if !visited.contains(&next) && pages.len() < MAX_INDEX_PAGES => url = next,
+            _ => break,
+        }
+    }
+
+    session.save_cookie(&url)?;
+    Ok(pages)
+}
+
+/// What became of one problem of a bulk download.
+enum Outcome {
+    Saved,
+    Skipped(String),
+    Failed(Error),
+    NotTried,
+}
+
+impl Outcome {
+    fn label(&self) -> (&'static str, String) {
+        match self {
+            Outcome::Saved => ("OK", "OK".green().to_string()),
+            Outcome::Skipped(_) => ("SKIP", "SKIP".yellow().to_string()),
+            Outcome::Failed(_) => ("FAIL", "FAIL".red().to_string()),
+            Outcome::NotTried => ("-", "-".to_string()),
+        }
+    }
+
+    fn detail(&self) -> String {
+        match self {
+            Outcome::Saved | Outcome::NotTried => String::new(),
+            Outcome::Skipped(path) => path.clone(),
+            Outcome::Failed(e) => e.to_string(),
+        }
+    }
+}
+
+/// Spaces out the requests of every worker by at least `interval`, so that a
+/// long range does not hammer the site.
+struct RateLimit {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimit {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn wait(&self) {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        if *next > now {
+            thread::sleep(*next - now);
+        }
+        *next = Instant::now() + self.interval;
+    }
+}
+
+/// Fetches the pages on up to `jobs` threads, while the problems are saved
+/// one at a time here so that their output does not interleave.
+fn download_all(
+    ids: &[String],
+    urls: &[String],
+    session: &Session,
+    jobs: usize,
+    setup: &Setup,
+    config: &Config,
+) -> Result<(), Error> {
+    let mut outcomes = ids.iter().map(|_| Outcome::NotTried).collect::<Vec<_>>();
+
+    let queue = Mutex::new(VecDeque::new());
+    for (i, id) in ids.iter().enumerate() {
+        let testcase_dir = Path::new(&config.problem_dir(id)).join(config.testcase_dir());
+        if testcase_dir.is_dir() {
+            let path = config.problem_dir(id);
+            println!(
+                "[{}] {}",
+                *INFO_LABEL,
+                tr!(Msg::AlreadyDownloaded, id, path)
+            );
+            outcomes[i] = Outcome::Skipped(path);
+        } else {
+            queue.lock().unwrap_or_else(|e| e.into_inner()).push_back(i);
+        }
+    }
+
+    let total = queue.lock().unwrap_or_else(|e| e.into_inner()).len();
+    let rate_limit = RateLimit::new(if session.is_offline() {
+        Duration::ZERO
+    } else {
+        config.download_interval()
+    });
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, MAX_JOBS).min(total) {
+            let sender = sender.clone();
+            let (queue, rate_limit) = (&queue, &rate_limit);
+            scope.spawn(move || {
+                loop {
+                    let Some(i) = queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front()
+                    else {
+                        break;
+                    };
+                    rate_limit.wait();
+                    let result = session.fetch(&urls[i]);
+                    if matches!(result, Err(Error::Unauthenticated)) {
+                        // every other page would fail the same way
+                        queue.lock().unwrap_or_else(|e| e.into_inner()).clear();
+                    }
+                    if sender.send((i, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for (done, (i, result)) in receiver.into_iter().enumerate() {
+            let id = &ids[i];
+            println!(
+                "[{}] {}",
+                *INFO_LABEL,
+                tr!(Msg::DownloadProgress, id, done + 1, total)
+            );
+            outcomes[i] =
+                match result.and_then(|html| save_problem(&html, id, &urls[i], setup, config)) {
+                    Ok(()) => Outcome::Saved,
+                    Err(e) => {
+                        debug!("failed to download problem {}: {}", id, e);
+                        Outcome::Failed(e)
+                    }
+                };
         }
+    });
+
+    if let Some(url) = urls
+        .iter()
+        .zip(&outcomes)
+        .find_map(|(url, outcome)| matches!(outcome, Outcome::Saved).then_some(url))
+    {
+        session.save_cookie(url)?;
     }
 
+    let rows = ids
+        .iter()
+        .zip(&outcomes)
+        .map(|(id, outcome)| vec![id.clone(), outcome.label().0.to_string(), outcome.detail()])
+        .collect::<Vec<_>>();
+    println!();
+    let header = [
+        tr!(Msg::ColumnProblem),
+        tr!(Msg::ColumnResult),
+        tr!(Msg::ColumnDetail),
+    ];
+    let header = header.iter().map(String::as_str).collect::<Vec<_>>();
+    print_table(&header, &rows, |row, column, cell| {
+        if column == 1 {
+            outcomes[row].label().1
+        } else {
+            cell.to_string()
+        }
+    });
+
+    if outcomes
+        .iter()
+        .any(|outcome| matches!(outcome, Outcome::Failed(Error::Unauthenticated)))
+    {
+        return Err(Error::Unauthenticated);
+    }
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, Outcome::Failed(_) | Outcome::NotTried))
+        .count();
+    if failed > 0 {
+        return Err(Error::DownloadsFailed(failed));
+    }
     Ok(())
 }
 
-fn valid_problem_url(url: &str) -> Result<bool, Error> {
-    let re = Regex::new(r"^https://recursionist.io/dashboard/problems/\d+$")
-        .map_err(|_| Error::Internal("Regex compile error in valid_email".to_string()))?;
+/// The problems listed in a `--from-file` file: one or more per line, with
+/// blank lines and `#` comments ignored.
+fn read_targets(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Expands the problems given as numbers, URLs, ranges such as `1..50` (both
+/// ends included) or comma-separated lists of them, into problem URLs.
+fn parse_targets(site: &Site, targets: &[String]) -> Result<Vec<String>, Error> {
+    let mut urls = Vec::new();
+
+    for target in targets.iter().flat_map(|t| t.split(',')) {
+        let target = target.trim();
+        if target.is_empty() {
+            continue;
+        }
+
+        let expanded = if let Some((start, end)) = target.split_once("..") {
+            let end = end.strip_prefix('=').unwrap_or(end);
+            match (start.trim().parse::<u32>(), end.trim().parse::<u32>()) {
+                (Ok(start), Ok(end)) if start <= end => (start..=end)
+                    .map(|n| create_url(site, &n.to_string()))
+                    .collect(),
+                _ => return Err(Error::InvalidTarget(target.to_string())),
+            }
+        } else if is_natural_number(target) {
+            vec![create_url(site, target)]
+        } else if valid_problem_url(site, target)? {
+            vec![target.to_string()]
+        } else {
+            return Err(Error::UrlIncorrectFormat);
+        };
+
+        for url in expanded {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    }
+
+    Ok(urls)
+}
 
-    Ok(re.is_match(url.trim()))
+fn valid_problem_url(site: &Site, url: &str) -> Result<bool, Error> {
+    Ok(site.problem_id(url).is_some())
 }
 
-fn is_login_successful(location: &str) -> bool {
-    location == "https://recursionist.io/dashboard"
+fn is_login_successful(site: &Site, location: &str) -> bool {
+    location == site.dashboard_url()
 }
 
-fn create_url(num_str: &str) -> String {
-    let prefix = "https://recursionist.io/dashboard/problems/";
-    format!("{}{}", prefix, num_str)
+fn create_url(site: &Site, num_str: &str) -> String {
+    site.problem_url(num_str)
 }
 
 fn is_natural_number(s: &str) -> bool {
     s.parse::<u32>().is_ok()
 }
 
-fn extract_url_number(url: &str) -> Result<String, Error> {
-    let re = Regex::new(r"/problems/(\d+)$")
-        .map_err(|_| Error::Internal("Regex compile error in extract_url_number".to_string()))?;
-    let caps = re.captures(url).ok_or(Error::UrlIncorrectFormat)?;
-    let matched = caps.get(1).ok_or(Error::Internal(format!(
-        "Capture group not found in extract_url_number for URL: {}",
-        url
-    )))?;
-    Ok(matched.as_str().to_string())
+/// A client sending the cookie saved at login, shared by every page fetched
+/// in one command instead of reading the cookie file again for each.
+pub struct Session {
+    client: Client,
+    jar: Arc<Jar>,
+    cookie_header: String,
+    cache: Option<PageCache>,
+    retries: u32,
 }
 
-fn fetch_problem_page(url: &str) -> Result<HTML, Error> {
-    let jar = Arc::new(Jar::default());
-    let client = create_client(Redirect::ON, &jar)?;
+impl Session {
+    pub fn load(network: &NetworkSettings) -> Result<Self, Error> {
+        let cookie_path = get_cookie_path()?;
 
-    let cookie_path = get_cookie_path()?;
+        let cookies = load_cookies(cookie_path)?;
+        let cookie_header = format_cookie_header(cookies);
 
-    let cookies = load_cookies(cookie_path)?;
-    let cookie_header = format_cookie_header(cookies);
+        Self::with_cookie(cookie_header, network)
+    }
 
-    println!("[{}] GET: {}", *NETWORK_LABEL, url);
-    let res = get_page_with_cookie(&client, url, &cookie_header)?;
+    /// A session fetching pages through the page cache. Offline, it does not
+    /// need a login.
+    pub fn load_cached(mode: CacheMode, network: &NetworkSettings) -> Result<Self, Error> {
+        let session = match mode {
+            CacheMode::Offline => Self::with_cookie(String::new(), network)?,
+            _ => Self::load(network)?,
+        };
+        Ok(session.with_cache(PageCache::open(mode)?))
+    }
 
-    let final_url = res.url().as_str();
-    if final_url != url {
-        return Err(Error::AuthenticationError);
+    /// A session sending `cookie_header` instead of the saved cookie.
+    pub fn with_cookie(cookie_header: String, network: &NetworkSettings) -> Result<Self, Error> {
+        let jar = Arc::new(Jar::default());
+        let client = create_client(Redirect::ON, &jar, network)?;
+
+        Ok(Self {
+            client,
+            jar,
+            cookie_header,
+            cache: None,
+            retries: network.retries,
+        })
     }
 
-    println!("[{}] {}", *NETWORK_LABEL, res.status());
-    println!();
+    pub fn with_cache(self, cache: PageCache) -> Self {
+        Self {
+            cache: Some(cache),
+            ..self
+        }
+    }
 
-    let url_parsed = Url::parse(url).map_err(|_| Error::UrlIncorrectFormat)?;
+    pub fn is_offline(&self) -> bool {
+        self.cache
+            .as_ref()
+            .is_some_and(|cache| cache.mode() == CacheMode::Offline)
+    }
 
-    let cookie = jar.cookies(&url_parsed).ok_or(Error::NoCookie)?;
+    /// Fetches a page, or takes it from the cache when the session has one:
+    /// a cached page is used when the site answers 304 Not Modified or
+    /// cannot be reached.
+    pub fn fetch(&self, url: &str) -> Result<HTML, Error> {
+        let cached = self.cache.as_ref().and_then(|cache| cache.lookup(url));
+        if self.is_offline() {
+            return cached
+                .map(|page| page.body)
+                .ok_or(Error::NotCached(url.to_string()));
+        }
 
-    let response_cookie = cookie
-        .to_str()
-        .map_err(|_| Error::CookieNotUtf8)?
-        .to_string();
+        let builder = self.client.get(url).header("Cookie", &self.cookie_header);
+        let builder = match &cached {
+            Some(page) => page.conditional(builder),
+            None => builder,
+        };
+        let res = match (self.get(builder), cached) {
+            (Ok(res), cached) if res.status() == StatusCode::NOT_MODIFIED => {
+                if let Some(page) = cached {
+                    debug!("{} is not modified", url);
+                    return Ok(page.body);
+                }
+                res
+            }
+            (Ok(res), _) => res,
+            (Err(e), Some(page)) if e.is_unreachable() => {
+                warn!(
+                    "failed to fetch {}, the page cached at {} is used: {}",
+                    url,
+                    page.fetched_at(),
+                    e
+                );
+                return Ok(page.body);
+            }
+            (Err(e), _) => return Err(e),
+        };
+
+        let final_url = res.url().as_str();
+        if final_url != url {
+            debug!("redirected to {}", final_url);
+            return Err(Error::Unauthenticated);
+        }
+
+        let status = res.status();
+        if !status.is_success() {
+            return Err(Error::HttpStatus(status.as_u16(), url.to_string()));
+        }
+        let headers = res.headers().clone();
+        let body = res.text()?;
+        if let Some(cache) = &self.cache {
+            cache.store(url, &headers, &body)?;
+        }
+        Ok(body)
+    }
+
+    /// Fetches a JSON document, such as the state of a submission.
+    pub fn fetch_json(&self, url: &str) -> Result<serde_json::Value, Error> {
+        let res = self.get(
+            self.client
+                .get(url)
+                .header("Cookie", &self.cookie_header)
+                .header(ACCEPT, "application/json"),
+        )?;
+
+        if res.url().as_str() != url {
+            debug!("redirected to {}", res.url());
+            return Err(Error::Unauthenticated);
+        }
 
-    save_cookie_to_file(response_cookie)?;
+        let body = res.error_for_status()?.text()?;
+        serde_json::from_str(&body).map_err(|e| Error::UnexpectedResponse(e.to_string()))
+    }
+
+    /// Posts a form the way the page at `referer` does, with the CSRF token
+    /// found in that page. Returns the URL it ended at and the body.
+    pub fn post_form(
+        &self,
+        url: &str,
+        referer: &str,
+        form: &[(&str, &str)],
+    ) -> Result<(String, String), Error> {
+        let page = self.fetch(referer)?;
+        let token = extract_csrf_token(&page)?;
+
+        let mut form = form.to_vec();
+        form.push(("_token", &token));
+
+        let origin = Url::parse(referer)
+            .map_err(|_| Error::UrlIncorrectFormat)?
+            .origin()
+            .ascii_serialization();
+        let res = send(
+            self.client
+                .post(url)
+                .form(&form)
+                .header("Cookie", &self.cookie_header)
+                .header("X-CSRF-TOKEN", &token)
+                .header(ACCEPT, "application/json")
+                .header("Referer", referer)
+                .header("Origin", origin),
+        )?
+        .error_for_status()?;
+
+        let final_url = res.url().to_string();
+        Ok((final_url, res.text()?))
+    }
 
-    let body = res.text()?;
-    Ok(body)
+    /// Sends a GET, sending it again after a timeout, a dropped connection or
+    /// a busy or briefly unavailable site, waiting longer before each retry or
+    /// as long as the site asks in `Retry-After`.
+    fn get(&self, builder: RequestBuilder) -> Result<Response, Error> {
+        let mut attempt = 0;
+        loop {
+            let request = builder
+                .try_clone()
+                .ok_or(Error::Internal("GET request cannot be cloned".to_string()))?;
+            let (reason, wait) = match send(request) {
+                Ok(res) if attempt < self.retries && is_retryable_status(res.status()) => {
+                    let wait = match retry_after(res.headers()) {
+                        Some(wait) => wait,
+                        None if res.headers().contains_key(RETRY_AFTER) => return Ok(res),
+                        None => backoff(attempt),
+                    };
+                    (format!("{}: HTTP {}", res.url(), res.status()), wait)
+                }
+                Err(e @ (Error::Timeout(_) | Error::Network(_))) if attempt < self.retries => {
+                    (e.to_string(), backoff(attempt))
+                }
+                result => return result,
+            };
+
+            attempt += 1;
+            warn!(
+                "{}, retrying in {:.1}s ({}/{})",
+                reason,
+                wait.as_secs_f64(),
+                attempt,
+                self.retries
+            );
+            thread::sleep(wait);
+        }
+    }
+
+    /// Saves the cookies the site sent back for `url`, keeping the login fresh.
+    /// A site that sends none, such as a local stand-in server, keeps the
+    /// saved cookie as it is.
+    pub fn save_cookie(&self, url: &str) -> Result<(), Error> {
+        let url_parsed = Url::parse(url).map_err(|_| Error::UrlIncorrectFormat)?;
+
+        let Some(cookie) = self.jar.cookies(&url_parsed) else {
+            debug!("no cookie was sent back from {}", url);
+            return Ok(());
+        };
+
+        let response_cookie = cookie
+            .to_str()
+            .map_err(|_| Error::CookieNotUtf8)?
+            .to_string();
+
+        save_cookie_to_file(response_cookie)
+    }
 }
 
 fn save_cookie_to_file(cookie: String) -> Result<(), Error> {
     let cookie_path = get_cookie_path()?;
     save_to_file(&cookie_path, &cookie)?;
-    println!("[{}] Save cookie to: {:?}", *INFO_LABEL, cookie_path);
+    info!("Save cookie to: {:?}", cookie_path);
     Ok(())
 }
 
-fn create_client(disable_redirect: Redirect, jar: &Arc<Jar>) -> Result<Client, Error> {
-    let builder = Client::builder().cookie_provider(jar.clone());
+fn create_client(
+    disable_redirect: Redirect,
+    jar: &Arc<Jar>,
+    network: &NetworkSettings,
+) -> Result<Client, Error> {
+    let builder = configure_client(Client::builder().cookie_provider(jar.clone()), network)?;
 
     Ok(match disable_redirect {
         Redirect::ON => builder,
@@ -254,15 +794,50 @@ fn format_cookie_header(cookies: Cookie) -> String {
         .join("; ")
 }
 
-fn get_page_with_cookie(
-    client: &Client,
-    url: &str,
-    cookie_header: &str,
-) -> Result<Response, Error> {
-    let res = client.get(url).header("Cookie", cookie_header).send()?;
+/// Sends the request, logging the request line at `-v` and the headers with
+/// credentials redacted at `-vv`.
+fn send(builder: RequestBuilder) -> Result<Response, Error> {
+    let (client, request) = builder.build_split();
+    let request = request?;
+
+    info!(target: NETWORK, "{}: {}", request.method(), request.url());
+    log_headers(">", request.headers());
+
+    let res = client.execute(request)?;
+
+    info!(target: NETWORK, "{}", res.status());
+    log_headers("<", res.headers());
+
     Ok(res)
 }
 
+fn log_headers(direction: &str, headers: &HeaderMap) {
+    if !log::log_enabled!(target: NETWORK, log::Level::Debug) {
+        return;
+    }
+
+    for (name, value) in headers {
+        let value = value.to_str().unwrap_or("<binary>");
+        debug!(target: NETWORK, "{} {}: {}", direction, name, redact_header(name.as_str(), value));
+    }
+}
+
+/// The CSRF token of a page, from `<meta name="csrf-token">` or the `_token`
+/// field of its forms.
+fn extract_csrf_token(html: &str) -> Result<String, Error> {
+    let doc = Html::parse_document(html);
+    let selector = Selector::parse(r#"meta[name="csrf-token"]"#)
+        .map_err(|e| Error::Selector(e.to_string().into()))?;
+
+    match doc
+        .select(&selector)
+        .find_map(|e| e.value().attr("content"))
+    {
+        Some(token) => Ok(token.to_string()),
+        None => extract_token_from_html(html),
+    }
+}
+
 fn extract_token_from_html(html: &str) -> Result<String, Error> {
     let doc = Html::parse_document(html);
     let selector = Selector::parse(r#"input[name="_token"]"#).map_err(|_| {
@@ -281,7 +856,7 @@ fn extract_token_from_html(html: &str) -> Result<String, Error> {
 }
 
 fn load_cookies<P: AsRef<Path>>(path: P) -> Result<Cookie, Error> {
-    println!("[{}] Load cookie from: {:?}", *INFO_LABEL, path.as_ref());
+    info!("Load cookie from: {:?}", path.as_ref());
     let file = File::open(path)?;
     let reader = BufReader::new(file);
     let mut cookies = Cookie::new();
@@ -301,11 +876,118 @@ fn load_cookies<P: AsRef<Path>>(path: P) -> Result<Cookie, Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        config::SiteConfig,
+        mock_server::{Reply, mock_server},
+    };
+
+    /// A hand-written problem page, not a saved copy of the site. It assumes
+    /// the page has the title in `<h1>` and a `<link rel="canonical">` to the
+    /// problem; labeled values (`<dt>難易度</dt><dd>初級</dd>`, `入力のデータ型：
+    /// ...`); one `<p>` per sample with the text `f(3,2) --> 1` and a
+    /// `span[data-case]`; and the statement in `.problem-statement`.
+    const SAVED_PAGE: &str = include_str!("../tests/fixtures/problem-1.html");
+
+    #[test]
+    fn test_find_problem_id() {
+        let site = Site::default();
+        assert_eq!(
+            find_problem_id(SAVED_PAGE, &site).unwrap(),
+            Some("1".to_string())
+        );
+
+        let html = r#"<link rel="canonical" href="https://recursionist.io/dashboard/problems/42">"#;
+        assert_eq!(
+            find_problem_id(html, &site).unwrap(),
+            Some("42".to_string())
+        );
+
+        let html = "<!-- saved from url=(0046)https://recursionist.io/dashboard/problems/7 -->";
+        assert_eq!(find_problem_id(html, &site).unwrap(), Some("7".to_string()));
+
+        assert_eq!(find_problem_id("<p>no url</p>", &site).unwrap(), None);
+    }
+
+    #[test]
+    fn test_find_problem_id_with_custom_problem_path() {
+        let config = SiteConfig {
+            problem_path: Some("/p/{id}/view".to_string()),
+            ..SiteConfig::default()
+        };
+        let site = Site::from_config(&config, None).unwrap();
+
+        let html = r#"<meta property="og:url" content="https://example.com/p/42/view">"#;
+        assert_eq!(
+            find_problem_id(html, &site).unwrap(),
+            Some("42".to_string())
+        );
+
+        let html = r#"<link rel="canonical" href="https://example.com/dashboard/problems/42">"#;
+        assert_eq!(find_problem_id(html, &site).unwrap(), None);
+    }
+
+    #[test]
+    fn test_saved_page_test_cases() {
+        let cases = get_test_cases(SAVED_PAGE).unwrap();
+        let info = get_problem_info(SAVED_PAGE).unwrap();
+
+        assert_eq!(cases.len(), 2);
+        assert_eq!(info.title.as_deref(), Some("最低気温"));
+        assert_eq!(info.function_name.as_deref(), Some("getLowestTemperature"));
+        assert_eq!(info.arg_count, 2);
+        assert_eq!(info.difficulty.as_deref(), Some("初級"));
+        assert_eq!(info.category.as_deref(), Some("数学"));
+        assert_eq!(info.time_limit.as_deref(), Some("1秒"));
+        assert_eq!(
+            info.signature.as_deref(),
+            Some("getLowestTemperature(integer high, integer diff) -> integer")
+        );
+
+        let statement = get_statement(SAVED_PAGE).unwrap().unwrap();
+        assert!(statement.contains("#### 制約\n\n- -100 <= high <= 100\n"));
+        assert!(statement.contains("```\nfunction getLowestTemperature(high, diff) {\n"));
+    }
+
+    #[test]
+    fn test_parse_targets() {
+        let site = Site::default();
+        let targets = [
+            "1..3",
+            "5,https://recursionist.io/dashboard/problems/7",
+            "2..=2",
+        ]
+        .map(String::from);
+
+        assert_eq!(
+            parse_targets(&site, &targets).unwrap(),
+            [1, 2, 3, 5, 7].map(|n| create_url(&site, &n.to_string()))
+        );
+
+        assert!(matches!(
+            parse_targets(&site, &["3..1".to_string()]),
+            Err(Error::InvalidTarget(_))
+        ));
+        assert!(matches!(
+            parse_targets(&site, &["1..x".to_string()]),
+            Err(Error::InvalidTarget(_))
+        ));
+        assert!(matches!(
+            parse_targets(&site, &["https://example.com/problems/1".to_string()]),
+            Err(Error::UrlIncorrectFormat)
+        ));
+    }
+
+    #[test]
+    fn test_read_targets() {
+        let contents = "# warm-up\n1..3\n\n10, 12  # arrays\n";
+
+        assert_eq!(read_targets(contents), ["1..3", "10, 12"]);
+    }
 
     #[test]
     fn test_create_url() {
         let num_str = "1";
-        let actual = create_url(num_str);
+        let actual = create_url(&Site::default(), num_str);
 
         let expected = "https://recursionist.io/dashboard/problems/1";
 
@@ -330,12 +1012,6 @@ mod tests {
         assert!(!is_natural_number(num_str));
     }
 
-    #[test]
-    fn test_extract_url_number() {
-        let url = "https://example.com/dashboard/pjroblems/42";
-        assert_eq!(extract_url_number(url).unwrap(), "42".to_string());
-    }
-
     #[test]
     fn test_load_cookies() {
         let path = "./tests/cookie.jar";
@@ -375,33 +1051,130 @@ mod tests {
     fn test_is_login_successful() {
         let location = "https://recursionist.io/dashboard";
 
-        assert!(is_login_successful(location));
+        assert!(is_login_successful(&Site::default(), location));
 
         let wrong_location = "https://recursionist.io/";
 
-        assert!(!is_login_successful(wrong_location));
+        assert!(!is_login_successful(&Site::default(), wrong_location));
     }
 
     #[test]
     fn test_valid_problem_url() {
         let url = "https://recursionist.io/dashboard/problems/1";
 
-        assert!(valid_problem_url(url).unwrap());
+        assert!(valid_problem_url(&Site::default(), url).unwrap());
 
         let url = "https://recursionist.io/dashboard/problems/1000";
 
-        assert!(valid_problem_url(url).unwrap());
+        assert!(valid_problem_url(&Site::default(), url).unwrap());
 
         let url = "https://example.com/dashboard/problems/1";
 
-        assert!(!valid_problem_url(url).unwrap());
+        assert!(!valid_problem_url(&Site::default(), url).unwrap());
 
         let url = "123https://recursionist.io/dashboard/problems/1";
 
-        assert!(!valid_problem_url(url).unwrap());
+        assert!(!valid_problem_url(&Site::default(), url).unwrap());
 
         let url = "https://recursionist.io/dashboard/problems/100.000";
 
-        assert!(!valid_problem_url(url).unwrap());
+        assert!(!valid_problem_url(&Site::default(), url).unwrap());
+    }
+
+    #[test]
+    fn test_fetch_revalidates_the_cache() {
+        use std::{env, fs};
+
+        let (base, server) = mock_server(vec![
+            Reply::ok("text/html", "page").header("ETag", "\"v1\""),
+            Reply::new("304 Not Modified"),
+        ]);
+        let url = format!("{}/dashboard/problems/1", base);
+
+        let dir = env::temp_dir().join(format!("rlr-fetch-cache-{}", std::process::id()));
+        let session = |mode| {
+            Session::with_cookie(String::new(), &NetworkSettings::default())
+                .unwrap()
+                .with_cache(PageCache::in_dir(dir.clone(), mode))
+        };
+
+        assert!(matches!(
+            session(CacheMode::Offline).fetch(&url),
+            Err(Error::NotCached(_))
+        ));
+        assert_eq!(session(CacheMode::Revalidate).fetch(&url).unwrap(), "page");
+        assert_eq!(session(CacheMode::Revalidate).fetch(&url).unwrap(), "page");
+        assert_eq!(session(CacheMode::Offline).fetch(&url).unwrap(), "page");
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_retries_busy_site() {
+        let (base, server) = mock_server(vec![
+            Reply::new("503 Service Unavailable").header("Retry-After", "0"),
+            Reply::ok("text/html", "page"),
+            Reply::new("404 Not Found"),
+        ]);
+
+        let session = Session::with_cookie(String::new(), &NetworkSettings::default()).unwrap();
+        let url = format!("{}/dashboard/problems/1", base);
+        assert_eq!(session.fetch(&url).unwrap(), "page");
+
+        let url = format!("{}/dashboard/problems/2", base);
+        assert!(matches!(
+            session.fetch(&url),
+            Err(Error::HttpStatus(404, failed)) if failed == url
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_network_errors() {
+        let network = NetworkSettings {
+            retries: 0,
+            ..NetworkSettings::default()
+        };
+        let session = Session::with_cookie(String::new(), &network).unwrap();
+
+        assert!(matches!(
+            session.fetch("http://rlr.invalid/dashboard/problems/1"),
+            Err(Error::Dns(host)) if host == "rlr.invalid"
+        ));
+
+        // a server speaking plain HTTP where TLS is expected
+        let (base, server) = mock_server(vec![Reply::new("400 Bad Request")]);
+        let url = base.replace("http://", "https://");
+        assert!(matches!(session.fetch(&url), Err(Error::Tls(..))));
+        server.join().unwrap();
+
+        // nothing listens on the port any more
+        let (base, server) = mock_server(vec![Reply::ok("text/html", "page")]);
+        let url = format!("{}/dashboard/problems/1", base);
+        assert_eq!(session.fetch(&url).unwrap(), "page");
+        server.join().unwrap();
+        assert!(matches!(session.fetch(&url), Err(Error::Network(_))));
+    }
+
+    #[test]
+    fn test_fetch_through_proxy() {
+        use crate::network::Proxies;
+
+        let (proxy, server) = mock_server(vec![Reply::ok("text/html", "page")]);
+
+        let network = NetworkSettings {
+            proxies: Proxies::all(&proxy, None),
+            ..NetworkSettings::default()
+        };
+        let session = Session::with_cookie(String::new(), &network).unwrap();
+        let url = "http://rlr.invalid/dashboard/problems/1";
+        assert_eq!(session.fetch(url).unwrap(), "page");
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with(&format!("GET {} ", url)));
     }
 }
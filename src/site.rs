@@ -0,0 +1,231 @@
+use regex::Regex;
+
+use crate::{config::SiteConfig, error::Error};
+
+/// Overrides `site.base_url`, e.g. to point rlr at a local server.
+pub const BASE_URL_ENV: &str = "RLR_BASE_URL";
+
+const DEFAULT_BASE_URL: &str = "https://recursionist.io";
+const DEFAULT_LOGIN_PATH: &str = "/login";
+const DEFAULT_DASHBOARD_PATH: &str = "/dashboard";
+const DEFAULT_PROBLEM_PATH: &str = "/dashboard/problems/{id}";
+const DEFAULT_PROBLEM_INDEX_PATH: &str = "/dashboard/problems";
+const DEFAULT_SUBMIT_PATH: &str = "/dashboard/problems/{id}/submit";
+const DEFAULT_SUBMISSION_PATH: &str = "/dashboard/submissions/{id}";
+
+/// Placeholder of the number in the path patterns.
+const ID: &str = "{id}";
+
+/// Where the pages of the site are. Paths are joined to the base URL, and
+/// `{id}` in a path pattern is replaced with a problem or submission number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Site {
+    base_url: String,
+    login_path: String,
+    dashboard_path: String,
+    problem_path: String,
+    problem_index_path: String,
+    submit_path: String,
+    submission_path: String,
+}
+
+impl Default for Site {
+    fn default() -> Self {
+        Self::new(DEFAULT_BASE_URL)
+    }
+}
+
+impl Site {
+    /// The site at `base_url` with the default paths.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            login_path: DEFAULT_LOGIN_PATH.to_string(),
+            dashboard_path: DEFAULT_DASHBOARD_PATH.to_string(),
+            problem_path: DEFAULT_PROBLEM_PATH.to_string(),
+            problem_index_path: DEFAULT_PROBLEM_INDEX_PATH.to_string(),
+            submit_path: DEFAULT_SUBMIT_PATH.to_string(),
+            submission_path: DEFAULT_SUBMISSION_PATH.to_string(),
+        }
+    }
+
+    /// The `[site]` settings, with the base URL taken from `base_url_env`
+    /// (the value of `RLR_BASE_URL`) when it is set.
+    pub fn from_config(config: &SiteConfig, base_url_env: Option<String>) -> Result<Self, Error> {
+        let base_url = base_url_env
+            .filter(|url| !url.trim().is_empty())
+            .or(config.base_url.clone())
+            .unwrap_or(DEFAULT_BASE_URL.to_string());
+        if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+            return Err(Error::Config(format!(
+                "site.base_url must start with http:// or https://: {}",
+                base_url
+            )));
+        }
+
+        let mut site = Self::new(base_url.trim());
+        let paths = [
+            (&mut site.login_path, &config.login_path, false),
+            (&mut site.dashboard_path, &config.dashboard_path, false),
+            (&mut site.problem_path, &config.problem_path, true),
+            (
+                &mut site.problem_index_path,
+                &config.problem_index_path,
+                false,
+            ),
+            (&mut site.submit_path, &config.submit_path, true),
+            (&mut site.submission_path, &config.submission_path, true),
+        ];
+        for (path, configured, has_id) in paths {
+            if let Some(configured) = configured {
+                if has_id && !configured.contains(ID) {
+                    return Err(Error::Config(format!(
+                        "the path must contain {}: {}",
+                        ID, configured
+                    )));
+                }
+                *path = configured.clone();
+            }
+        }
+
+        Ok(site)
+    }
+
+    /// Every setting of this site, as `[site]` would give it.
+    pub fn to_config(&self) -> SiteConfig {
+        SiteConfig {
+            base_url: Some(self.base_url.clone()),
+            login_path: Some(self.login_path.clone()),
+            dashboard_path: Some(self.dashboard_path.clone()),
+            problem_path: Some(self.problem_path.clone()),
+            problem_index_path: Some(self.problem_index_path.clone()),
+            submit_path: Some(self.submit_path.clone()),
+            submission_path: Some(self.submission_path.clone()),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn login_url(&self) -> String {
+        self.url(&self.login_path)
+    }
+
+    pub fn dashboard_url(&self) -> String {
+        self.url(&self.dashboard_path)
+    }
+
+    pub fn problem_url(&self, id: &str) -> String {
+        self.url(&self.problem_path.replace(ID, id))
+    }
+
+    pub fn problem_index_url(&self) -> String {
+        self.url(&self.problem_index_path)
+    }
+
+    pub fn submit_url(&self, id: &str) -> String {
+        self.url(&self.submit_path.replace(ID, id))
+    }
+
+    pub fn submission_url(&self, id: &str) -> String {
+        self.url(&self.submission_path.replace(ID, id))
+    }
+
+    /// The problem number of a problem page URL of this site.
+    pub fn problem_id(&self, url: &str) -> Option<String> {
+        self.match_id(&self.problem_path, url)
+    }
+
+    /// The submission number of a submission URL of this site.
+    pub fn submission_id(&self, url: &str) -> Option<String> {
+        self.match_id(&self.submission_path, url)
+    }
+
+    /// Matches a link to a problem page, absolute or relative to the site,
+    /// capturing the problem number.
+    pub fn problem_link(&self) -> Result<Regex, Error> {
+        let path = regex::escape(&self.problem_path).replace(&regex::escape(ID), r"(\d+)");
+        Regex::new(&format!(r"{}/?(?:[?#].*)?$", path))
+            .map_err(|_| Error::Internal("Regex compile error in problem_link".to_string()))
+    }
+
+    fn match_id(&self, path: &str, url: &str) -> Option<String> {
+        let pattern = format!(
+            "^{}$",
+            regex::escape(&self.url(path)).replace(&regex::escape(ID), r"(\d+)")
+        );
+        Regex::new(&pattern)
+            .ok()?
+            .captures(url.trim())
+            .map(|caps| caps[1].to_string())
+    }
+
+    fn url(&self, path: &str) -> String {
+        if path.starts_with('/') {
+            format!("{}{}", self.base_url, path)
+        } else {
+            format!("{}/{}", self.base_url, path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_site() {
+        let site = Site::default();
+
+        assert_eq!(site.login_url(), "https://recursionist.io/login");
+        assert_eq!(
+            site.problem_url("12"),
+            "https://recursionist.io/dashboard/problems/12"
+        );
+        assert_eq!(
+            site.problem_id("https://recursionist.io/dashboard/problems/12"),
+            Some("12".to_string())
+        );
+        assert_eq!(
+            site.problem_id("https://example.com/dashboard/problems/12"),
+            None
+        );
+        assert_eq!(
+            site.submission_id("https://recursionist.io/dashboard/submissions/5"),
+            Some("5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_site_from_config() {
+        let config = SiteConfig {
+            base_url: Some("https://staging.example.com/".to_string()),
+            problem_path: Some("/p/{id}/view".to_string()),
+            ..SiteConfig::default()
+        };
+
+        let site = Site::from_config(&config, None).unwrap();
+        assert_eq!(
+            site.problem_url("3"),
+            "https://staging.example.com/p/3/view"
+        );
+        assert_eq!(
+            site.problem_id("https://staging.example.com/p/3/view"),
+            Some("3".to_string())
+        );
+        assert!(site.problem_link().unwrap().is_match("/p/3/view?tab=1"));
+
+        let site = Site::from_config(&config, Some("http://127.0.0.1:8080".to_string())).unwrap();
+        assert_eq!(site.dashboard_url(), "http://127.0.0.1:8080/dashboard");
+
+        let config = SiteConfig {
+            submit_path: Some("/submit".to_string()),
+            ..SiteConfig::default()
+        };
+        assert!(matches!(
+            Site::from_config(&config, None),
+            Err(Error::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,566 @@
+use log::{info, warn};
+use std::path::Path;
+
+use crate::{
+    config::Config,
+    error::Error,
+    file::save_to_file,
+    i18n::{Msg, tr},
+    language::find_profile,
+    layout::InputLayout,
+    messages::SUCCESS_LABEL,
+    parser::{ProblemInfo, ValueType},
+    template::{TemplateVars, render},
+};
+
+/// Used when the page has no sample to take the function name from.
+const DEFAULT_FUNCTION_NAME: &str = "solve";
+
+/// Languages `rlr download --stub` can generate a source file for.
+///
+/// The generated program reads the arguments from stdin in the layout
+/// `save_test_cases` writes them in, and prints the result in the RecursionCS
+/// format (`true`, `[1,2,3]`, `"a b"`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StubLanguage {
+    Python,
+    JavaScript,
+    Ruby,
+    Rust,
+    Cpp,
+    Java,
+}
+
+impl StubLanguage {
+    /// Takes the name of the language profile the stub is written for.
+    pub fn from_name(name: &str) -> Result<Self, Error> {
+        match name {
+            "python" => Ok(StubLanguage::Python),
+            "javascript" => Ok(StubLanguage::JavaScript),
+            "ruby" => Ok(StubLanguage::Ruby),
+            "rust" => Ok(StubLanguage::Rust),
+            "cpp" => Ok(StubLanguage::Cpp),
+            "java" => Ok(StubLanguage::Java),
+            _ => Err(Error::StubUnsupported(name.to_string())),
+        }
+    }
+
+    fn profile_name(&self) -> &'static str {
+        match self {
+            StubLanguage::Python => "python",
+            StubLanguage::JavaScript => "javascript",
+            StubLanguage::Ruby => "ruby",
+            StubLanguage::Rust => "rust",
+            StubLanguage::Cpp => "cpp",
+            StubLanguage::Java => "java",
+        }
+    }
+
+    fn template(&self) -> &'static str {
+        match self {
+            StubLanguage::Python => include_str!("stubs/python.tmpl"),
+            StubLanguage::JavaScript => include_str!("stubs/javascript.tmpl"),
+            StubLanguage::Ruby => include_str!("stubs/ruby.tmpl"),
+            StubLanguage::Rust => include_str!("stubs/rust.tmpl"),
+            StubLanguage::Cpp => include_str!("stubs/cpp.tmpl"),
+            StubLanguage::Java => include_str!("stubs/java.tmpl"),
+        }
+    }
+
+    /// Helpers the program needs to read `layout`. The typed languages split
+    /// a line of literals with the `split_literals` of their template.
+    fn input_helpers(&self, layout: InputLayout) -> &'static str {
+        match (self, layout) {
+            (StubLanguage::Python, InputLayout::Line) => include_str!("stubs/line/python.tmpl"),
+            (StubLanguage::JavaScript, InputLayout::Line) => {
+                include_str!("stubs/line/javascript.tmpl")
+            }
+            (StubLanguage::Ruby, InputLayout::Line) => include_str!("stubs/line/ruby.tmpl"),
+            (StubLanguage::Python, InputLayout::Counted) => {
+                include_str!("stubs/counted/python.tmpl")
+            }
+            (StubLanguage::JavaScript, InputLayout::Counted) => {
+                include_str!("stubs/counted/javascript.tmpl")
+            }
+            (StubLanguage::Ruby, InputLayout::Counted) => include_str!("stubs/counted/ruby.tmpl"),
+            (StubLanguage::Rust, InputLayout::Counted) => include_str!("stubs/counted/rust.tmpl"),
+            (StubLanguage::Cpp, InputLayout::Counted) => include_str!("stubs/counted/cpp.tmpl"),
+            (StubLanguage::Java, InputLayout::Counted) => include_str!("stubs/counted/java.tmpl"),
+            _ => "",
+        }
+    }
+
+    /// Statements reading stdin: into `args` for the dynamic languages, which
+    /// parse each token as JSON, and into one token per argument (`tokens`) for
+    /// the typed ones. A line is split at the spaces outside string and array
+    /// literals, as `InputLayout::format` joins them. `depths` are the array
+    /// depths the counted layout needs.
+    fn read_args(&self, layout: InputLayout, depths: &[usize]) -> String {
+        let depths = depths
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let lines = match (self, layout) {
+            (StubLanguage::Python, InputLayout::Line) => {
+                vec!["args = [parse(token) for token in split_literals(sys.stdin.read())]".to_string()]
+            }
+            (StubLanguage::Python, InputLayout::Counted) => vec![format!(
+                "args = read_counted(sys.stdin.read().splitlines(), [{}])",
+                depths
+            )],
+            (StubLanguage::Python, _) => {
+                vec!["args = [parse(line) for line in sys.stdin.read().splitlines()]".to_string()]
+            }
+            (StubLanguage::JavaScript, InputLayout::Line) => {
+                vec!["const args = splitLiterals(input).map(parse);".to_string()]
+            }
+            (StubLanguage::JavaScript, InputLayout::Counted) => vec![format!(
+                "const args = readCounted(input.replace(/\\n$/, \"\").split(/\\r?\\n/), [{}]);",
+                depths
+            )],
+            (StubLanguage::JavaScript, _) => vec![
+                "const args = input.replace(/\\n$/, \"\").split(/\\r?\\n/).map(parse);".to_string(),
+            ],
+            (StubLanguage::Ruby, InputLayout::Line) => {
+                vec!["args = split_literals($stdin.read).map { |token| parse(token) }".to_string()]
+            }
+            (StubLanguage::Ruby, InputLayout::Counted) => vec![format!(
+                "args = read_counted($stdin.read.lines(chomp: true), [{}])",
+                depths
+            )],
+            (StubLanguage::Ruby, _) => {
+                vec!["args = $stdin.read.lines(chomp: true).map { |line| parse(line) }".to_string()]
+            }
+            (StubLanguage::Rust, InputLayout::Line) => {
+                vec!["let tokens = split_literals(&input, char::is_whitespace);".to_string()]
+            }
+            (StubLanguage::Rust, InputLayout::Counted) => vec![
+                format!("let counted = read_counted(&input, &[{}]);", depths),
+                "let tokens: Vec<&str> = counted.iter().map(|s| s.as_str()).collect();".to_string(),
+            ],
+            (StubLanguage::Rust, _) => {
+                vec!["let tokens: Vec<&str> = input.lines().collect();".to_string()]
+            }
+            (StubLanguage::Cpp, InputLayout::Line) => vec![
+                "std::string input{std::istreambuf_iterator<char>(std::cin), std::istreambuf_iterator<char>()};".to_string(),
+                "std::vector<std::string> tokens = split_literals(input, is_space);".to_string(),
+            ],
+            (StubLanguage::Cpp, InputLayout::Counted) => vec![
+                "std::vector<std::string> lines;".to_string(),
+                "std::string line;".to_string(),
+                "while (std::getline(std::cin, line)) lines.push_back(line);".to_string(),
+                format!(
+                    "std::vector<std::string> tokens = read_counted(lines, {{{}}});",
+                    depths
+                ),
+            ],
+            (StubLanguage::Cpp, _) => vec![
+                "std::vector<std::string> tokens;".to_string(),
+                "std::string line;".to_string(),
+                "while (std::getline(std::cin, line)) tokens.push_back(line);".to_string(),
+            ],
+            (StubLanguage::Java, layout) => {
+                let read = "new BufferedReader(new InputStreamReader(System.in)).lines().collect(Collectors.toList())";
+                match layout {
+                    InputLayout::Line => vec![format!(
+                        "List<String> tokens = splitLiterals(String.join(\"\\n\", {}), Character::isWhitespace);",
+                        read
+                    )],
+                    InputLayout::Counted => vec![format!(
+                        "List<String> tokens = readCounted({}, new int[] {{{}}});",
+                        read, depths
+                    )],
+                    _ => vec![format!("List<String> tokens = {};", read)],
+                }
+            }
+        };
+
+        let indent = match self {
+            StubLanguage::Java => "        ",
+            StubLanguage::Rust | StubLanguage::Cpp | StubLanguage::Python => "    ",
+            StubLanguage::JavaScript | StubLanguage::Ruby => "  ",
+        };
+        lines
+            .iter()
+            .map(|line| format!("{}{}", indent, line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn is_typed(&self) -> bool {
+        matches!(
+            self,
+            StubLanguage::Rust | StubLanguage::Cpp | StubLanguage::Java
+        )
+    }
+
+    fn type_name(&self, t: &ValueType) -> String {
+        match (self, t) {
+            (StubLanguage::Rust, ValueType::Int) => "i64".to_string(),
+            (StubLanguage::Rust, ValueType::Float) => "f64".to_string(),
+            (StubLanguage::Rust, ValueType::Bool) => "bool".to_string(),
+            (StubLanguage::Rust, ValueType::Array(e)) => format!("Vec<{}>", self.type_name(e)),
+            (StubLanguage::Rust, _) => "String".to_string(),
+            (StubLanguage::Cpp, ValueType::Int) => "long long".to_string(),
+            (StubLanguage::Cpp, ValueType::Float) => "double".to_string(),
+            (StubLanguage::Cpp, ValueType::Bool) => "bool".to_string(),
+            (StubLanguage::Cpp, ValueType::Array(e)) => {
+                format!("std::vector<{}>", self.type_name(e))
+            }
+            (StubLanguage::Cpp, _) => "std::string".to_string(),
+            (StubLanguage::Java, ValueType::Int) => "long".to_string(),
+            (StubLanguage::Java, ValueType::Float) => "double".to_string(),
+            (StubLanguage::Java, ValueType::Bool) => "boolean".to_string(),
+            (StubLanguage::Java, ValueType::Array(e)) => match **e {
+                ValueType::Bool => "Boolean[]".to_string(),
+                _ => format!("{}[]", self.type_name(e)),
+            },
+            (StubLanguage::Java, _) => "String".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn default_value(&self, t: &ValueType) -> String {
+        let value = match (self, t) {
+            (_, ValueType::Int) => "0",
+            (_, ValueType::Float) => "0.0",
+            (StubLanguage::Python, ValueType::Bool) => "False",
+            (_, ValueType::Bool) => "false",
+            (StubLanguage::Rust, ValueType::Str | ValueType::Unknown) => "String::new()",
+            (_, ValueType::Str) => "\"\"",
+            (StubLanguage::Rust, ValueType::Array(_)) => "Vec::new()",
+            (StubLanguage::Cpp, ValueType::Array(_)) => "{}",
+            (StubLanguage::Java, ValueType::Array(e)) => {
+                return format!("new {}[0]", self.type_name(e).trim_end_matches("[]"));
+            }
+            (_, ValueType::Array(_)) => "[]",
+            (StubLanguage::Python, ValueType::Unknown) => "None",
+            (StubLanguage::JavaScript, ValueType::Unknown) => "null",
+            (StubLanguage::Ruby, ValueType::Unknown) => "nil",
+            (_, ValueType::Unknown) => "\"\"",
+        };
+        value.to_string()
+    }
+
+    fn param(&self, name: &str, t: &ValueType) -> String {
+        match self {
+            StubLanguage::Rust => format!("{}: {}", name, self.type_name(t)),
+            StubLanguage::Cpp | StubLanguage::Java => format!("{} {}", self.type_name(t), name),
+            _ => name.to_string(),
+        }
+    }
+
+    /// Statement that reads the `index`-th token from `tokens` into `name`.
+    fn parse_arg(&self, index: usize, name: &str, t: &ValueType) -> String {
+        let type_name = self.type_name(t);
+        match self {
+            StubLanguage::Rust => {
+                let token = format!("tokens[{}]", index);
+                let value = match t {
+                    ValueType::Array(e) => format!(
+                        "items({}).iter().map(|s| {}).collect()",
+                        token,
+                        rust_parse("s", e)
+                    ),
+                    _ => rust_parse(&token, t),
+                };
+                format!("    let {}: {} = {};", name, type_name, value)
+            }
+            StubLanguage::Cpp => {
+                let token = format!("tokens[{}]", index);
+                match t {
+                    ValueType::Array(e) => format!(
+                        "    {} {};\n    for (const auto& item : items({})) {}.push_back({});",
+                        type_name,
+                        name,
+                        token,
+                        name,
+                        cpp_parse("item", e)
+                    ),
+                    _ => format!("    {} {} = {};", type_name, name, cpp_parse(&token, t)),
+                }
+            }
+            StubLanguage::Java => {
+                let token = format!("tokens.get({})", index);
+                let value = match t {
+                    ValueType::Array(e) => {
+                        format!("Arrays.stream(items({})){}", token, java_collect(e))
+                    }
+                    _ => java_parse(&token, t),
+                };
+                format!("        {} {} = {};", type_name, name, value)
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// The expression printing what `call` returns. A result the typed
+    /// languages only have as text, such as a nested array, is printed as is.
+    fn print_result(&self, call: &str, return_type: &ValueType) -> String {
+        let raw = self.is_typed()
+            && *return_type != ValueType::Str
+            && self.simplify(return_type) == ValueType::Str;
+        match self {
+            _ if raw => call.to_string(),
+            StubLanguage::Rust => format!("{}.to_text()", call),
+            StubLanguage::Java => format!("toText({})", call),
+            _ => format!("to_text({})", call),
+        }
+    }
+
+    /// Typed languages get one level of arrays of scalars, anything deeper is
+    /// passed as the raw text.
+    fn simplify(&self, t: &ValueType) -> ValueType {
+        if !self.is_typed() {
+            return t.clone();
+        }
+        match t {
+            ValueType::Array(e) => match **e {
+                ValueType::Int | ValueType::Float | ValueType::Bool | ValueType::Str => t.clone(),
+                _ => ValueType::Str,
+            },
+            ValueType::Unknown => ValueType::Str,
+            _ => t.clone(),
+        }
+    }
+}
+
+fn rust_parse(token: &str, t: &ValueType) -> String {
+    match t {
+        ValueType::Int | ValueType::Float | ValueType::Bool => {
+            format!("{}.parse().unwrap()", token)
+        }
+        _ => format!("unquote({})", token),
+    }
+}
+
+fn cpp_parse(token: &str, t: &ValueType) -> String {
+    let function = match t {
+        ValueType::Int => "parse_int",
+        ValueType::Float => "parse_float",
+        ValueType::Bool => "parse_bool",
+        _ => "parse_str",
+    };
+    format!("{}({})", function, token)
+}
+
+fn java_parse(token: &str, t: &ValueType) -> String {
+    match t {
+        ValueType::Int => format!("Long.parseLong({})", token),
+        ValueType::Float => format!("Double.parseDouble({})", token),
+        ValueType::Bool => format!("Boolean.parseBoolean({})", token),
+        _ => format!("unquote({})", token),
+    }
+}
+
+fn java_collect(t: &ValueType) -> &'static str {
+    match t {
+        ValueType::Int => ".mapToLong(Long::parseLong).toArray()",
+        ValueType::Float => ".mapToDouble(Double::parseDouble).toArray()",
+        ValueType::Bool => ".map(Boolean::valueOf).toArray(Boolean[]::new)",
+        _ => ".map(Main::unquote).toArray(String[]::new)",
+    }
+}
+
+pub fn generate_stub(language: StubLanguage, info: &ProblemInfo, layout: InputLayout) -> String {
+    let names = (1..=info.arg_count)
+        .map(|i| format!("arg{}", i))
+        .collect::<Vec<_>>();
+    let types = (0..info.arg_count)
+        .map(|i| language.simplify(info.arg_types.get(i).unwrap_or(&ValueType::Unknown)))
+        .collect::<Vec<_>>();
+    let return_type = language.simplify(&info.return_type);
+    let depths = (0..info.arg_count)
+        .map(|i| info.arg_types.get(i).map_or(0, InputLayout::depth))
+        .collect::<Vec<_>>();
+
+    let params = names
+        .iter()
+        .zip(&types)
+        .map(|(name, t)| language.param(name, t))
+        .collect::<Vec<_>>();
+    let parse_args = names
+        .iter()
+        .zip(&types)
+        .enumerate()
+        .map(|(i, (name, t))| language.parse_arg(i, name, t))
+        .collect::<Vec<_>>();
+
+    let mut vars = TemplateVars::new();
+    vars.insert(
+        "function_name",
+        info.function_name
+            .clone()
+            .unwrap_or(DEFAULT_FUNCTION_NAME.to_string()),
+    );
+    vars.insert("params", params.join(", "));
+    vars.insert("call_args", names.join(", "));
+    vars.insert(
+        "print_result",
+        language.print_result(
+            &format!("{}({})", vars["function_name"], names.join(", ")),
+            &info.return_type,
+        ),
+    );
+    vars.insert("parse_args", parse_args.join("\n"));
+    vars.insert("return_type", language.type_name(&return_type));
+    vars.insert("default_return", language.default_value(&return_type));
+    vars.insert("read_args", language.read_args(layout, &depths));
+    vars.insert("input_helpers", language.input_helpers(layout).to_string());
+
+    render(language.template(), &vars)
+}
+
+/// Writes the stub as the source file of the language profile. A source file
+/// that already has contents, e.g. from a template, is left alone.
+pub fn write_stub(
+    language: StubLanguage,
+    info: &ProblemInfo,
+    layout: InputLayout,
+    dest_dir: &str,
+    config: &Config,
+) -> Result<(), Error> {
+    let profile = find_profile(language.profile_name(), config)?;
+    let path = Path::new(dest_dir).join(&profile.source);
+
+    if path.metadata().is_ok_and(|m| m.len() > 0) {
+        warn!(
+            "{} already exists, so the stub is not written",
+            path.display()
+        );
+        return Ok(());
+    }
+
+    info!("generate {} stub", language.profile_name());
+    save_to_file(&path, &generate_stub(language, info, layout))?;
+    println!("[{}] {}", *SUCCESS_LABEL, tr!(Msg::SavedTo, path.display()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::literal::Value;
+    use std::{
+        env, fs,
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    fn info() -> ProblemInfo {
+        ProblemInfo {
+            function_name: Some("sumOfList".to_string()),
+            arg_count: 2,
+            arg_types: vec![ValueType::Array(Box::new(ValueType::Int)), ValueType::Bool],
+            return_type: ValueType::Int,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_generate_python_stub() {
+        let stub = generate_stub(StubLanguage::Python, &info(), InputLayout::Line);
+
+        assert!(stub.contains("def sumOfList(arg1, arg2):\n"));
+        assert!(stub.contains("    return 0\n"));
+        assert!(stub.contains("print(to_text(sumOfList(*args)))"));
+    }
+
+    #[test]
+    fn test_generate_rust_stub() {
+        let stub = generate_stub(StubLanguage::Rust, &info(), InputLayout::Line);
+
+        assert!(stub.contains("fn sumOfList(arg1: Vec<i64>, arg2: bool) -> i64 {"));
+        assert!(stub.contains(
+            "    let arg1: Vec<i64> = items(tokens[0]).iter().map(|s| s.parse().unwrap()).collect();"
+        ));
+        assert!(stub.contains("    let arg2: bool = tokens[1].parse().unwrap();"));
+        assert!(!stub.contains("{{"));
+    }
+
+    #[test]
+    fn test_generate_java_stub_with_nested_array() {
+        let info = ProblemInfo {
+            arg_count: 1,
+            arg_types: vec![ValueType::Array(Box::new(ValueType::Array(Box::new(
+                ValueType::Int,
+            ))))],
+            return_type: ValueType::Array(Box::new(ValueType::Str)),
+            ..Default::default()
+        };
+
+        let stub = generate_stub(StubLanguage::Java, &info, InputLayout::Line);
+
+        assert!(stub.contains("static String[] solve(String arg1) {"));
+        assert!(stub.contains("return new String[0];"));
+    }
+
+    #[test]
+    fn test_generate_stub_for_counted_layout() {
+        let stub = generate_stub(StubLanguage::Cpp, &info(), InputLayout::Counted);
+
+        assert!(stub.contains("std::vector<std::string> tokens = read_counted(lines, {1, 0});"));
+        assert!(stub.contains("std::string read_counted("));
+
+        let stub = generate_stub(StubLanguage::Python, &info(), InputLayout::Lines);
+
+        assert!(
+            stub.contains("    args = [parse(line) for line in sys.stdin.read().splitlines()]")
+        );
+        assert!(!stub.contains("read_counted"));
+    }
+
+    #[test]
+    fn test_rust_stub_reads_and_prints_string_literals() {
+        let info = ProblemInfo {
+            function_name: Some("repeat".to_string()),
+            arg_count: 2,
+            arg_types: vec![ValueType::Array(Box::new(ValueType::Str)), ValueType::Int],
+            return_type: ValueType::Array(Box::new(ValueType::Str)),
+            ..Default::default()
+        };
+        let stub = generate_stub(StubLanguage::Rust, &info, InputLayout::Line).replace(
+            "    Vec::new()\n",
+            "    (0..arg2).flat_map(|_| arg1.clone()).collect()\n",
+        );
+
+        let dir = env::temp_dir().join(format!("rlr-stub-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("main.rs");
+        let binary = dir.join("main");
+        fs::write(&source, stub).unwrap();
+        let status = Command::new("rustc")
+            .arg("-o")
+            .arg(&binary)
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let words = ["a b", "c,d", "\"e\" [f]"].map(|s| Value::Str(s.to_string()));
+        let input = InputLayout::Line.format(&[Value::Array(words.to_vec()), Value::Int(2)]);
+        let mut child = Command::new(&binary)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let expected = Value::Array([words.clone(), words].concat()).to_string();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), expected);
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert!(StubLanguage::from_name("cobol").is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use std::{env, thread, time::Duration, time::Instant};
+
+use log::debug;
+
+use crate::{
+    config::Config,
+    error::Error,
+    extract::extract_code,
+    file::read_file,
+    i18n::{Msg, tr},
+    judge::{JudgeResult, Verdict, print_results},
+    language::{detect_profile, profile_for_file},
+    messages::{INFO_LABEL, SUCCESS_LABEL},
+    problem::Problem,
+    request::Session,
+    site::Site,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const POLL_TIMEOUT: Duration = Duration::from_secs(120);
+/// States of a submission that is not judged yet.
+const PENDING_STATUSES: &[&str] = &["pending", "queued", "waiting", "judging", "running"];
+
+pub struct SubmitOptions {
+    /// Source file, found like `judge` does when not given.
+    pub file: Option<String>,
+    /// Problem number, read from `problem.toml` when not given.
+    pub id: Option<String>,
+    /// Language name sent to the site, the profile name by default.
+    pub language: Option<String>,
+    /// Send the whole file instead of the code `extract` picks.
+    pub whole_file: bool,
+    /// Wait for the verdict.
+    pub wait: bool,
+}
+
+/// Sends the solution of the problem in the current directory and shows the
+/// verdict of the site like `judge` shows its results.
+pub fn submit(options: &SubmitOptions, config: &Config) -> Result<(), Error> {
+    let dir = env::current_dir()?;
+    let problem_id = match &options.id {
+        Some(id) => id.clone(),
+        None => Problem::load(&dir)?
+            .map(|problem| problem.id)
+            .ok_or(Error::NoProblemId)?,
+    };
+
+    let (file, detected) = match &options.file {
+        Some(file) => (file.clone(), profile_for_file(file, config)),
+        None => {
+            let (name, _, file) = detect_profile(&dir, config).ok_or(Error::NoSourceFile)?;
+            (file, Some(name))
+        }
+    };
+    let language = options
+        .language
+        .clone()
+        .or(detected)
+        .ok_or(Error::NoLanguage(file.clone()))?;
+
+    let code = if options.whole_file {
+        read_file(&file)?
+    } else {
+        extract_code(&file, None)?
+    };
+
+    println!(
+        "[{}] {}",
+        *INFO_LABEL,
+        tr!(Msg::Submitting, problem_id, file, language)
+    );
+
+    let site = config.site()?;
+    let session = Session::load(&config.network())?;
+    let submission_url = send_submission(&session, &site, &problem_id, &language, &code)?;
+    session.save_cookie(site.base_url())?;
+    println!(
+        "[{}] {}",
+        *SUCCESS_LABEL,
+        tr!(Msg::Submitted, submission_url)
+    );
+
+    if !options.wait {
+        return Ok(());
+    }
+
+    println!("[{}] {}", *INFO_LABEL, Msg::WaitingVerdict);
+    let results = wait_for_verdict(&session, &submission_url, POLL_INTERVAL, POLL_TIMEOUT)?;
+    println!();
+    print_results(&results);
+
+    Ok(())
+}
+
+/// Posts the code from the problem page and returns the URL of the submission,
+/// given as JSON (`{"submission_id": 1}`) or by redirecting to it.
+fn send_submission(
+    session: &Session,
+    site: &Site,
+    problem_id: &str,
+    language: &str,
+    code: &str,
+) -> Result<String, Error> {
+    let problem_url = site.problem_url(problem_id);
+    let submit_url = site.submit_url(problem_id);
+
+    let (final_url, body) = session.post_form(
+        &submit_url,
+        &problem_url,
+        &[("language", language), ("code", code)],
+    )?;
+    debug!("submitted, ended at {}", final_url);
+
+    let id = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|json| {
+            ["submission_id", "id"]
+                .iter()
+                .find_map(|key| match &json[key] {
+                    serde_json::Value::Number(n) => Some(n.to_string()),
+                    serde_json::Value::String(s) => Some(s.clone()),
+                    _ => None,
+                })
+        });
+    let id = id
+        .or_else(|| site.submission_id(&final_url))
+        .ok_or(Error::UnexpectedResponse(format!(
+            "no submission in the response from {}",
+            final_url
+        )))?;
+
+    Ok(site.submission_url(&id))
+}
+
+fn wait_for_verdict(
+    session: &Session,
+    submission_url: &str,
+    interval: Duration,
+    timeout: Duration,
+) -> Result<Vec<JudgeResult>, Error> {
+    let start = Instant::now();
+    loop {
+        let state = session.fetch_json(submission_url)?;
+        if let Some(results) = parse_verdict(&state)? {
+            return Ok(results);
+        }
+        if start.elapsed() >= timeout {
+            return Err(Error::VerdictTimeout);
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Reads the state of a submission, `None` while it is being judged:
+///
+/// ```json
+/// {"status": "finished", "verdict": "WA",
+///  "results": [{"name": "case-1", "verdict": "AC", "time": 0.01, "memory": 1024}]}
+/// ```
+///
+/// Without per-case results, the overall verdict makes a single row.
+fn parse_verdict(state: &serde_json::Value) -> Result<Option<Vec<JudgeResult>>, Error> {
+    let status = state["status"].as_str().unwrap_or_default().to_lowercase();
+    if PENDING_STATUSES.contains(&status.as_str()) {
+        debug!("submission is {}", status);
+        return Ok(None);
+    }
+
+    let cases = ["results", "cases", "testcases"]
+        .iter()
+        .find_map(|key| state[key].as_array());
+    let results = match cases {
+        Some(cases) => cases
+            .iter()
+            .enumerate()
+            .map(|(i, case)| case_result(case, &format!("case-{}", i + 1)))
+            .collect(),
+        None if state["verdict"].is_string() => vec![case_result(state, "submission")],
+        None => {
+            return Err(Error::UnexpectedResponse(format!(
+                "no verdict in the submission: {}",
+                state
+            )));
+        }
+    };
+    Ok(Some(results))
+}
+
+fn case_result(case: &serde_json::Value, default_name: &str) -> JudgeResult {
+    let name = ["name", "case"]
+        .iter()
+        .find_map(|key| case[key].as_str())
+        .unwrap_or(default_name);
+    let code = ["verdict", "result"]
+        .iter()
+        .find_map(|key| case[key].as_str())
+        .unwrap_or_default();
+    let time = match case["time"].as_f64() {
+        Some(secs) => secs,
+        None => case["time_ms"].as_f64().unwrap_or(0.0) / 1000.0,
+    };
+    let message = case["message"].as_str().unwrap_or_default();
+
+    // verdicts judge does not have, such as CE or MLE, are shown as RE with the code
+    let (verdict, reason) = match Verdict::from_code(code) {
+        Some(verdict) => (verdict, message.to_string()),
+        None => (
+            Verdict::RE,
+            format!("{} {}", code, message).trim().to_string(),
+        ),
+    };
+
+    JudgeResult::new(
+        name.to_string(),
+        verdict,
+        Duration::from_secs_f64(time.max(0.0)),
+        case["memory"].as_u64(),
+        reason,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{Reply, mock_server};
+    use crate::network::NetworkSettings;
+
+    #[test]
+    fn test_submit_and_wait_for_verdict() {
+        let (base, server) = mock_server(vec![
+            Reply::ok(
+                "text/html",
+                r#"<html><head><meta name="csrf-token" content="tok"></head></html>"#,
+            ),
+            Reply::ok("application/json", r#"{"submission_id": 7}"#),
+            Reply::ok("application/json", r#"{"status": "judging"}"#),
+            Reply::ok(
+                "application/json",
+                r#"{"status": "finished", "results": [
+                    {"name": "case-1", "verdict": "AC", "time": 0.012, "memory": 2048},
+                    {"name": "case-2", "verdict": "MLE", "time": 0.5}
+                ]}"#,
+            ),
+        ]);
+        let session = Session::with_cookie(
+            "recursion_session=abc".to_string(),
+            &NetworkSettings::default(),
+        )
+        .unwrap();
+
+        let site = Site::new(&base);
+
+        let url = send_submission(&session, &site, "1", "python", "print(1)\n").unwrap();
+        assert_eq!(url, format!("{}/dashboard/submissions/7", base));
+
+        let results =
+            wait_for_verdict(&session, &url, Duration::ZERO, Duration::from_secs(5)).unwrap();
+        let requests = server.join().unwrap();
+
+        assert!(requests[0].starts_with("GET /dashboard/problems/1 "));
+        assert!(requests[1].starts_with("POST /dashboard/problems/1/submit "));
+        assert!(requests[1].contains("x-csrf-token: tok"));
+        assert!(requests[1].contains("cookie: recursion_session=abc"));
+        assert!(requests[1].ends_with("language=python&code=print%281%29%0A&_token=tok"));
+        assert!(requests[3].starts_with("GET /dashboard/submissions/7 "));
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].verdict, Verdict::AC);
+        assert_eq!(results[0].memory_kb, Some(2048));
+        assert_eq!(results[1].verdict, Verdict::RE);
+        assert_eq!(results[1].reason, "MLE");
+    }
+
+    #[test]
+    fn test_parse_verdict() {
+        let pending = serde_json::json!({"status": "Queued"});
+        assert!(parse_verdict(&pending).unwrap().is_none());
+
+        let overall = serde_json::json!({"status": "finished", "verdict": "Accepted"});
+        let results = parse_verdict(&overall).unwrap().unwrap();
+        assert_eq!(results[0].case_name, "submission");
+        assert_eq!(results[0].verdict, Verdict::AC);
+
+        let broken = serde_json::json!({"status": "finished"});
+        assert!(matches!(
+            parse_verdict(&broken),
+            Err(Error::UnexpectedResponse(_))
+        ));
+    }
+}
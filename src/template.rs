@@ -0,0 +1,531 @@
+use log::{debug, warn};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    config::{CONFIG_FILE_NAME, Config},
+    error::Error,
+    file::{get_config_dir, save_to_file},
+    i18n::{Msg, tr},
+    language::{Profile, find_profile},
+    messages::{INFO_LABEL, SUCCESS_LABEL},
+    parser::ProblemInfo,
+};
+
+/// Directory holding the named templates, both in the workspace and in the
+/// user config directory.
+const LIBRARY_DIR: &str = "templates";
+
+static VARIABLE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap());
+
+/// Values available as `{{name}}` in template files and file names.
+pub type TemplateVars = BTreeMap<&'static str, String>;
+
+pub fn template_vars(problem_id: &str, url: &str, info: &ProblemInfo) -> TemplateVars {
+    let mut vars = TemplateVars::new();
+    vars.insert("problem_id", problem_id.to_string());
+    vars.insert("url", url.to_string());
+    vars.insert("title", info.title.clone().unwrap_or_default());
+    vars.insert(
+        "function_name",
+        info.function_name.clone().unwrap_or_default(),
+    );
+    vars.insert("arg_count", info.arg_count.to_string());
+    vars.insert("signature", info.signature.clone().unwrap_or_default());
+    vars.insert("category", info.category.clone().unwrap_or_default());
+    vars.insert("difficulty", info.difficulty.clone().unwrap_or_default());
+    vars.insert("date", chrono::Local::now().format("%Y-%m-%d").to_string());
+    vars
+}
+
+/// Replaces `{{name}}` with its value. Unknown names are left untouched so
+/// that templates can still contain other `{{...}}` syntaxes.
+pub fn render(text: &str, vars: &TemplateVars) -> String {
+    VARIABLE
+        .replace_all(text, |caps: &Captures| match vars.get(&caps[1]) {
+            Some(value) => value.clone(),
+            None => {
+                warn!("unknown template variable: {}", &caps[0]);
+                caps[0].to_string()
+            }
+        })
+        .to_string()
+}
+
+/// Renders a file or directory name of a template. Path separators in the
+/// values become `_`, and a name that is not a single plain file name, such
+/// as `..`, is rejected, so the copy cannot leave the problem directory.
+fn render_file_name(name: &str, vars: &TemplateVars) -> Result<String, Error> {
+    let path_vars = vars
+        .iter()
+        .map(|(key, value)| (*key, value.replace(['/', '\\'], "_")))
+        .collect::<TemplateVars>();
+
+    let rendered = render(name, &path_vars);
+    if !is_plain_name(&rendered) {
+        return Err(Error::InvalidRenderedName(rendered));
+    }
+    Ok(rendered)
+}
+
+/// Whether `name` is one file or directory name, neither `.` nor `..`.
+fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+/// What gets copied into a new problem directory.
+#[derive(Debug, PartialEq)]
+pub enum Template {
+    Dir(PathBuf),
+    /// No template directory exists for the language, so only its source
+    /// file and the profile setting are created.
+    Language(String, Profile),
+}
+
+/// Picks the template in this order: `--template`, `download.template`, the
+/// template named after `judge.profile`, then `./template`.
+///
+/// A name is looked up in the template library first, then as a directory
+/// path, then as a language profile.
+pub fn resolve_template(name: Option<&str>, config: &Config) -> Result<Option<Template>, Error> {
+    Library::open()?.resolve(name, config)
+}
+
+pub fn template_list() -> Result<(), Error> {
+    Library::open()?.list();
+    Ok(())
+}
+
+pub fn template_add(name: &str, dir: &str, global: bool) -> Result<(), Error> {
+    Library::open()?.add(name, dir, global)
+}
+
+pub fn template_remove(name: &str, global: bool) -> Result<(), Error> {
+    Library::open()?.remove(name, global)
+}
+
+/// The named templates: a `templates` directory in the current directory or
+/// any of its ancestors (the workspace), and one in the user config dir.
+struct Library {
+    cwd: PathBuf,
+    config_dir: Option<PathBuf>,
+}
+
+impl Library {
+    fn open() -> Result<Self, Error> {
+        Ok(Self::in_dirs(env::current_dir()?, get_config_dir().ok()))
+    }
+
+    /// The library seen from `cwd`, with the user config in `config_dir`.
+    fn in_dirs(cwd: PathBuf, config_dir: Option<PathBuf>) -> Self {
+        Self { cwd, config_dir }
+    }
+
+    fn resolve(&self, name: Option<&str>, config: &Config) -> Result<Option<Template>, Error> {
+        if let Some(name) = name {
+            return self
+                .find(name, config)
+                .map(Some)
+                .ok_or(Error::TemplateNotFound(name.to_string()));
+        }
+
+        if let Some(name) = &config.download.template {
+            let template = self.find(name, config);
+            if template.is_none() {
+                warn!("template {} in the config was not found", name);
+            }
+            return Ok(template);
+        }
+
+        if let Some(profile) = &config.judge.profile
+            && let Some(template) = self.find(profile, config)
+        {
+            return Ok(Some(template));
+        }
+
+        let legacy = self.cwd.join(config.template_dir());
+        Ok(legacy.is_dir().then_some(Template::Dir(legacy)))
+    }
+
+    fn find(&self, name: &str, config: &Config) -> Option<Template> {
+        if let Some((_, path)) = self.templates().into_iter().find(|(n, _)| n == name) {
+            return Some(Template::Dir(path));
+        }
+
+        let path = self.cwd.join(name);
+        if path.is_dir() {
+            return Some(Template::Dir(path));
+        }
+
+        find_profile(name, config)
+            .ok()
+            .map(|profile| Template::Language(name.to_string(), profile))
+    }
+
+    /// Library directories, nearest first: every `templates` directory from
+    /// the current directory up to the root, then the one in the user config
+    /// dir.
+    fn dirs(&self) -> Vec<PathBuf> {
+        self.cwd
+            .ancestors()
+            .map(|dir| dir.join(LIBRARY_DIR))
+            .chain(self.config_dir.iter().map(|dir| dir.join(LIBRARY_DIR)))
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+
+    /// Every named template, a nearer one hiding a farther one of the same
+    /// name.
+    fn templates(&self) -> Vec<(String, PathBuf)> {
+        let mut templates: Vec<(String, PathBuf)> = Vec::new();
+
+        for dir in self.dirs() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut found = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .filter_map(|path| Some((path.file_name()?.to_str()?.to_string(), path.clone())))
+                .filter(|(name, _)| !templates.iter().any(|(n, _)| n == name))
+                .collect::<Vec<_>>();
+            found.sort();
+            templates.extend(found);
+        }
+
+        templates
+    }
+
+    /// Where `template add` and `template remove` work: the user config dir
+    /// when `global`, the current directory otherwise.
+    fn dir(&self, global: bool) -> Result<PathBuf, Error> {
+        let base = if global {
+            self.config_dir.clone().ok_or(Error::CookiePathUnvaliable)?
+        } else {
+            self.cwd.clone()
+        };
+        Ok(base.join(LIBRARY_DIR))
+    }
+
+    /// The directory of the template `name`. The name must be a single
+    /// directory name, so that no path outside the library is touched.
+    fn template(&self, name: &str, global: bool) -> Result<PathBuf, Error> {
+        if !is_plain_name(name) {
+            return Err(Error::InvalidTemplateName(name.to_string()));
+        }
+        Ok(self.dir(global)?.join(name))
+    }
+
+    fn list(&self) {
+        let templates = self.templates();
+
+        if templates.is_empty() {
+            println!("{}", Msg::NoTemplates);
+        }
+        for (name, path) in templates {
+            println!("{} ({})", name, path.display());
+        }
+    }
+
+    fn add(&self, name: &str, dir: &str, global: bool) -> Result<(), Error> {
+        let dest = self.template(name, global)?;
+
+        let src = self.cwd.join(dir);
+        if !src.is_dir() {
+            return Err(Error::TemplateNotFound(dir.to_string()));
+        }
+
+        if dest.exists() {
+            return Err(Error::TemplateExists(name.to_string()));
+        }
+
+        copy_all_files_recursive(&src, &dest, None)?;
+        println!(
+            "[{}] {}",
+            *SUCCESS_LABEL,
+            tr!(Msg::TemplateAdded, name, dest.display())
+        );
+        Ok(())
+    }
+
+    fn remove(&self, name: &str, global: bool) -> Result<(), Error> {
+        let path = self.template(name, global)?;
+        if !path.is_dir() {
+            return Err(Error::TemplateNotFound(name.to_string()));
+        }
+
+        fs::remove_dir_all(&path)?;
+        println!(
+            "[{}] {}",
+            *SUCCESS_LABEL,
+            tr!(Msg::TemplateRemoved, name, path.display())
+        );
+        Ok(())
+    }
+}
+
+pub fn copy_template(
+    template: &Template,
+    dest_dir: &str,
+    vars: &TemplateVars,
+) -> Result<(), Error> {
+    let dest_path = Path::new(dest_dir);
+    if !dest_path.exists() {
+        return Ok(());
+    }
+
+    match template {
+        Template::Dir(path) => {
+            println!(
+                "[{}] {}",
+                *INFO_LABEL,
+                tr!(Msg::TemplateUsed, path.display())
+            );
+            debug!("copy template {:?} to {:?}", path, dest_path);
+            copy_all_files_recursive(path, dest_path, Some(vars))
+        }
+        Template::Language(name, profile) => {
+            println!("[{}] {}", *INFO_LABEL, tr!(Msg::TemplateUsed, name));
+            write_language_template(name, profile, dest_path)
+        }
+    }
+}
+
+/// Creates an empty source file and a `rlr.toml` selecting the profile, so
+/// `rlr judge` in the problem directory uses that language.
+fn write_language_template(name: &str, profile: &Profile, dest: &Path) -> Result<(), Error> {
+    let source = dest.join(&profile.source);
+    if !source.exists() {
+        save_to_file(&source, "")?;
+    }
+
+    let mut config = Config::default();
+    config.judge.profile = Some(name.to_string());
+    let contents = toml::to_string(&config).map_err(|e| Error::Config(e.to_string()))?;
+    save_to_file(&dest.join(CONFIG_FILE_NAME), &contents)?;
+
+    Ok(())
+}
+
+/// Copies `src` into `dest`, rendering names and contents when `vars` is given.
+fn copy_all_files_recursive(
+    src: &Path,
+    dest: &Path,
+    vars: Option<&TemplateVars>,
+) -> Result<(), Error> {
+    if !dest.exists() {
+        fs::create_dir_all(dest)
+            .map_err(|_| Error::Internal("Failed to create directory".to_string()))?;
+    }
+
+    for entry in
+        fs::read_dir(src).map_err(|_| Error::Internal("Failed to read directory".to_string()))?
+    {
+        let entry =
+            entry.map_err(|_| Error::Internal("Failed to get directory entry".to_string()))?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let new_path = match (file_name.to_str(), vars) {
+            (Some(name), Some(vars)) => dest.join(render_file_name(name, vars)?),
+            _ => dest.join(&file_name),
+        };
+
+        if path.is_dir() {
+            copy_all_files_recursive(&path, &new_path, vars)?;
+        } else if path.is_file() {
+            if let Some(parent) = new_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|_| Error::Internal("Failed to create paret direcotry".to_string()))?;
+            }
+            copy_rendered_file(&path, &new_path, vars)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Text files are rendered, anything that is not UTF-8 is copied as is.
+fn copy_rendered_file(src: &Path, dest: &Path, vars: Option<&TemplateVars>) -> Result<(), Error> {
+    let bytes = fs::read(src).map_err(|_| Error::Internal("Failed to read file".to_string()))?;
+
+    match (String::from_utf8(bytes), vars) {
+        (Ok(text), Some(vars)) => fs::write(dest, render(&text, vars)),
+        (Ok(text), None) => fs::write(dest, text),
+        (Err(e), _) => fs::write(dest, e.into_bytes()),
+    }
+    .map_err(|_| Error::Internal("Failed to copy file".to_string()))?;
+
+    // keep the executable bit of scripts such as run.sh
+    let permissions = fs::metadata(src)?.permissions();
+    fs::set_permissions(dest, permissions)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> TemplateVars {
+        let info = ProblemInfo {
+            title: Some("最低気温".to_string()),
+            function_name: Some("getLowestTemperature".to_string()),
+            arg_count: 2,
+            ..Default::default()
+        };
+        template_vars("1", "https://recursionist.io/dashboard/problems/1", &info)
+    }
+
+    /// A library in an empty temp dir, with a workspace `work` holding the
+    /// current directory `work/p-1`, and the user config dir `config`.
+    fn temp_library(name: &str) -> (PathBuf, Library) {
+        let root = env::temp_dir().join(format!("rlr-{}-{}", name, std::process::id()));
+        let cwd = root.join("work").join("p-1");
+        fs::create_dir_all(&cwd).unwrap();
+        let library = Library::in_dirs(cwd, Some(root.join("config")));
+        (root, library)
+    }
+
+    #[test]
+    fn test_template_name_outside_the_library_is_rejected() {
+        let (root, library) = temp_library("template-names");
+
+        for name in ["..", "a/b", "/tmp/rlr-template", "."] {
+            for global in [false, true] {
+                assert!(
+                    matches!(
+                        library.add(name, ".", global),
+                        Err(Error::InvalidTemplateName(_))
+                    ),
+                    "template add {}",
+                    name
+                );
+                assert!(
+                    matches!(
+                        library.remove(name, global),
+                        Err(Error::InvalidTemplateName(_))
+                    ),
+                    "template remove {}",
+                    name
+                );
+            }
+        }
+        assert!(library.cwd.is_dir());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_add_and_remove_template() {
+        let (root, library) = temp_library("template-add");
+        fs::create_dir_all(library.cwd.join("mine")).unwrap();
+        fs::write(library.cwd.join("mine").join("main.py"), "").unwrap();
+
+        library.add("python", "mine", true).unwrap();
+        assert!(root.join("config/templates/python/main.py").is_file());
+        assert!(matches!(
+            library.add("python", "mine", true),
+            Err(Error::TemplateExists(_))
+        ));
+
+        library.remove("python", true).unwrap();
+        assert!(!root.join("config/templates/python").exists());
+        assert!(matches!(
+            library.remove("python", true),
+            Err(Error::TemplateNotFound(_))
+        ));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_render() {
+        let text = "// {{ title }} ({{url}})\nfn {{function_name}}() {} // {{arg_count}} args";
+
+        let expected = "// 最低気温 (https://recursionist.io/dashboard/problems/1)\nfn getLowestTemperature() {} // 2 args";
+
+        assert_eq!(render(text, &vars()), expected);
+    }
+
+    #[test]
+    fn test_resolve_template_falls_back_to_language() {
+        let (root, library) = temp_library("template-resolve");
+        let config = Config::default();
+
+        let template = library.resolve(Some("python"), &config).unwrap();
+        assert!(matches!(template, Some(Template::Language(name, _)) if name == "python"));
+
+        assert!(library.resolve(Some("no-such-template"), &config).is_err());
+        assert_eq!(library.resolve(None, &config).unwrap(), None);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_template_prefers_the_nearest_library() {
+        let (root, library) = temp_library("template-nearest");
+        let workspace = root.join("work/templates/python");
+        fs::create_dir_all(&workspace).unwrap();
+        fs::create_dir_all(root.join("config/templates/python")).unwrap();
+        fs::create_dir_all(root.join("config/templates/rust")).unwrap();
+
+        let config = Config::default();
+        assert_eq!(
+            library.resolve(Some("python"), &config).unwrap(),
+            Some(Template::Dir(workspace))
+        );
+        assert_eq!(
+            library.resolve(Some("rust"), &config).unwrap(),
+            Some(Template::Dir(root.join("config/templates/rust")))
+        );
+        assert_eq!(library.templates().len(), 2);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_rendered_names_stay_in_the_problem_directory() {
+        let root = env::temp_dir().join(format!("rlr-template-names-{}", std::process::id()));
+        let template = root.join("template");
+        let dest = root.join("problem").join("p-1");
+        fs::create_dir_all(template.join("{{title}}")).unwrap();
+        fs::write(template.join("{{title}}").join("{{title}}.txt"), "").unwrap();
+        fs::create_dir_all(&dest).unwrap();
+
+        let mut vars = vars();
+        vars.insert("title", "../../escaped".to_string());
+        copy_all_files_recursive(&template, &dest, Some(&vars)).unwrap();
+        assert!(
+            dest.join(".._.._escaped")
+                .join(".._.._escaped.txt")
+                .is_file()
+        );
+        assert!(!root.join("escaped").exists());
+
+        vars.insert("title", "..".to_string());
+        assert!(matches!(
+            copy_all_files_recursive(&template, &dest, Some(&vars)),
+            Err(Error::InvalidRenderedName(name)) if name == ".."
+        ));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_render_keeps_unknown_variables() {
+        let text = "p-{{problem_id}} {{unknown}}";
+
+        assert_eq!(render(text, &vars()), "p-1 {{unknown}}");
+    }
+}